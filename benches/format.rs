@@ -0,0 +1,266 @@
+//! Compares formatting sizes through `SizeFormatter`, which rounds with integer arithmetic,
+//! against the code it replaced, which divided as `f64` and left the rounding to `{:.*}`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use size::{Size, SizeFormatter};
+use std::fmt::Write;
+
+/// Sizes spread over every magnitude from bytes to exbibytes.
+fn sizes() -> Vec<u64> {
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    (0..1024)
+        .map(|i| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state >> (i % 64)
+        })
+        .collect()
+}
+
+/// The formatting code the crate had before `SizeFormatter`, copied from `Size::format()`
+/// and the tables it used: the base-two rules, `Unit` and `Style`, with the unit constants
+/// it needs.
+#[allow(clippy::all, dead_code)]
+#[rustfmt::skip]
+mod old {
+    use self::Unit::*;
+    use std::fmt;
+
+    const KIBIBYTE: u64 = 1 << 10;
+    const MEBIBYTE: u64 = 1 << 20;
+    const GIBIBYTE: u64 = 1 << 30;
+    const TEBIBYTE: u64 = 1 << 40;
+    const PEBIBYTE: u64 = 1 << 50;
+    const EXBIBYTE: u64 = 1 << 60;
+
+    #[derive(Debug)]
+    pub enum Unit {
+        Byte,
+        Kibibyte,
+        Kilobyte,
+        Mebibyte,
+        Megabyte,
+        Gibibyte,
+        Gigabyte,
+        Tebibyte,
+        Terabyte,
+        Pebibyte,
+        Petabyte,
+        Exbibyte,
+        Exabyte,
+    }
+
+    impl Unit {
+        fn text(&self) -> (&'static str, &'static str, &'static str, &'static str) {
+            match &self {
+                &Byte => ("byte", "Byte", "b", "B"),
+
+                &Kilobyte => ("kilobyte", "Kilobyte", "kb", "KB"),
+                &Megabyte => ("megabyte", "Megabyte", "mb", "MB"),
+                &Gigabyte => ("gigabyte", "Gigabyte", "gb", "GB"),
+                &Terabyte => ("terabyte", "Terabyte", "tb", "TB"),
+                &Petabyte => ("petabyte", "Petabyte", "pb", "PB"),
+                &Exabyte => ("exabyte", "Exabyte", "eb", "EB"),
+
+                &Kibibyte => ("kibibyte", "Kibibyte", "kib", "KiB"),
+                &Mebibyte => ("mebibyte", "Mebibyte", "mib", "MiB"),
+                &Gibibyte => ("gibibyte", "Gibibyte", "gib", "GiB"),
+                &Pebibyte => ("pebibyte", "Pebibyte", "pib", "PiB"),
+                &Tebibyte => ("tebibyte", "Tebibyte", "tib", "TiB"),
+                &Exbibyte => ("exbibyte", "Exbibyte", "eib", "EiB"),
+            }
+        }
+
+        fn format(&self, mut fmt: &mut fmt::Formatter, bytes: u64, style: &Style) -> fmt::Result {
+            match style {
+                Style::Smart => match &self {
+                    &Unit::Byte => self.format(&mut fmt, bytes, &Style::FullLowerCase),
+                    _ => self.format(&mut fmt, bytes, &Style::Abbreviated),
+                },
+                style @ _ => match bytes {
+                    1 => match style {
+                        Style::Smart => panic!("already covered above"),
+                        Style::FullLowerCase => write!(fmt, " {}", self.text().0),
+                        Style::Full => write!(fmt, " {}", self.text().1),
+                        Style::AbbreviatedLowerCase => write!(fmt, " {}", self.text().2),
+                        Style::Abbreviated => write!(fmt, " {}", self.text().3),
+                    },
+                    _ => match style {
+                        Style::Smart => panic!("already covered above"),
+                        Style::FullLowerCase => write!(fmt, " {}s", self.text().0),
+                        Style::Full => write!(fmt, " {}s", self.text().1),
+                        Style::AbbreviatedLowerCase => write!(fmt, " {}", self.text().2),
+                        Style::Abbreviated => write!(fmt, " {}", self.text().3),
+                    },
+                },
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    pub enum Style {
+        Abbreviated,
+        AbbreviatedLowerCase,
+        Full,
+        Smart,
+        FullLowerCase,
+    }
+
+    pub fn format(mut fmt: &mut fmt::Formatter, bytes: u64, style: &Style) -> fmt::Result {
+        let rule = match BASE2_RULES.binary_search_by_key(&bytes, |rule| rule.less_than) {
+            Ok(index) => &BASE2_RULES[index + 1],
+            Err(index) => &BASE2_RULES[index],
+        };
+
+        (rule.formatter)(&mut fmt, bytes)?;
+        rule.unit.format(&mut fmt, bytes, &style)?;
+
+        return Ok(());
+    }
+
+    struct FormatRule {
+        less_than: u64,
+        formatter: fn(&mut fmt::Formatter, bytes: u64) -> fmt::Result,
+        unit: Unit,
+    }
+
+    const BASE2_RULES: [FormatRule; 19] = [
+        FormatRule {
+            less_than: 0,
+            formatter: |_, _| panic!("u64 less than zero!"),
+            unit: Byte,
+        },
+        FormatRule {
+            less_than: 1 * KIBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes),
+            unit: Byte,
+        },
+        FormatRule {
+            less_than: 10 * KIBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * KIBIBYTE) as f64)),
+            unit: Kibibyte,
+        },
+        FormatRule {
+            less_than: 100 * KIBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * KIBIBYTE) as f64)),
+            unit: Kibibyte,
+        },
+        FormatRule {
+            less_than: 1 * MEBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * KIBIBYTE) as f64)),
+            unit: Kibibyte,
+        },
+        FormatRule {
+            less_than: 10 * MEBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * MEBIBYTE) as f64)),
+            unit: Mebibyte,
+        },
+        FormatRule {
+            less_than: 100 * MEBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * MEBIBYTE) as f64)),
+            unit: Mebibyte,
+        },
+        FormatRule {
+            less_than: 1 * GIBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * MEBIBYTE) as f64)),
+            unit: Mebibyte,
+        },
+        FormatRule {
+            less_than: 10 * GIBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * GIBIBYTE) as f64)),
+            unit: Gibibyte,
+        },
+        FormatRule {
+            less_than: 100 * GIBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * GIBIBYTE) as f64)),
+            unit: Gibibyte,
+        },
+        FormatRule {
+            less_than: 1 * TEBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * GIBIBYTE) as f64)),
+            unit: Gibibyte,
+        },
+        FormatRule {
+            less_than: 10 * TEBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * TEBIBYTE) as f64)),
+            unit: Tebibyte,
+        },
+        FormatRule {
+            less_than: 100 * TEBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * TEBIBYTE) as f64)),
+            unit: Tebibyte,
+        },
+        FormatRule {
+            less_than: 1 * PEBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * TEBIBYTE) as f64)),
+            unit: Tebibyte,
+        },
+        FormatRule {
+            less_than: 10 * PEBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * PEBIBYTE) as f64)),
+            unit: Pebibyte,
+        },
+        FormatRule {
+            less_than: 100 * PEBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * PEBIBYTE) as f64)),
+            unit: Pebibyte,
+        },
+        FormatRule {
+            less_than: 1 * EXBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * PEBIBYTE) as f64)),
+            unit: Pebibyte,
+        },
+        FormatRule {
+            less_than: 10 * EXBIBYTE,
+            formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * EXBIBYTE) as f64)),
+            unit: Exbibyte,
+        },
+        FormatRule {
+            less_than: u64::max_value(),
+            formatter: |fmt, bytes| write!(fmt, "{:0}", bytes as f64 / ((1u64 * EXBIBYTE) as f64)),
+            unit: Exbibyte,
+        },
+    ];
+
+    /// Displays a number of bytes like `Display` for the old `Size` did.
+    pub struct Size(pub u64);
+
+    impl fmt::Display for Size {
+        fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+            format(fmt, self.0, &Style::Smart)
+        }
+    }
+}
+
+fn format(c: &mut Criterion) {
+    let sizes = sizes();
+    let mut out = String::with_capacity(64);
+
+    let formatter = SizeFormatter::new();
+    c.bench_function("format integer", |b| {
+        b.iter(|| {
+            for &bytes in &sizes {
+                out.clear();
+                write!(
+                    out,
+                    "{}",
+                    formatter.display(Size::from_bytes(black_box(bytes).into()))
+                )
+                .unwrap();
+            }
+        })
+    });
+
+    c.bench_function("format f64", |b| {
+        b.iter(|| {
+            for &bytes in &sizes {
+                out.clear();
+                write!(out, "{}", old::Size(black_box(bytes))).unwrap();
+            }
+        })
+    });
+}
+
+criterion_group!(benches, format);
+criterion_main!(benches);
@@ -2,12 +2,12 @@
 // https://neosmart.net/blog/2018/prettysize-for-rust/
 extern crate size;
 
-use size::{MiB, Size};
+use size::{MiB, TypedSize};
 
 fn main() {
     let bytes = 42 * MiB;
     assert_eq!(bytes, 44040192);
 
-    let bytes = Size::Mebibytes(42);
+    let bytes = TypedSize::Mebibytes(42);
     assert_eq!(format!("{}", bytes), "42.0 MiB");
 }
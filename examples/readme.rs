@@ -1,19 +1,21 @@
 extern crate size;
-use size::{Base, Size, Style};
+use size::{Base, Size, Style, TypedSize};
+use std::convert::TryFrom;
 
+#[allow(clippy::inconsistent_digit_grouping)]
 fn main() {
     let byte_count = 42 * size::KiB;
     assert_eq!(43__008, byte_count);
 
-    let byte_count = Size::Kilobytes(42);
+    let byte_count = Size::from_kb(42);
     assert_eq!(42__000, byte_count.bytes());
 
-    // `Size` can take any numeric type you throw at it
-    let byte_count2 = Size::Mebibytes(0.040055);
+    // `TypedSize` can take any numeric type you throw at it
+    let byte_count2 = Size::try_from(TypedSize::Mebibytes(0.040055)).unwrap();
     assert_eq!(byte_count.bytes(), byte_count2.bytes());
 
     // And for those of you that haven't yet drank the base-two Kool-Aid:
-    let byte_count = Size::Kilobytes(42);
+    let byte_count = Size::from_kb(42);
     assert_eq!(byte_count.bytes(), 42_000);
 
     println!("{}, I say!", byte_count);
@@ -21,7 +23,7 @@ fn main() {
 
     println!(
         "{}, I meant!",
-        byte_count.to_string(Base::Base10, Style::Abbreviated)
+        byte_count.to_string_with(Base::Base10, Style::Abbreviated)
     );
     // prints "42 KB, I meant!"
 }
@@ -8,7 +8,7 @@ extern crate serde_derive;
 extern crate serde;
 extern crate bincode;
 
-use size::{Base, Size,Unit, Style};
+use size::{Base, TypedSize,Unit, Style};
 use std::str::FromStr;
 
 use bincode::deserialize;
@@ -29,6 +29,14 @@ pub enum UnitDef {
     Petabyte,
     Exbibyte,
     Exabyte,
+    Zettabyte,
+    Zebibyte,
+    Yottabyte,
+    Yobibyte,
+    Ronnabyte,
+    Robibyte,
+    Quettabyte,
+    Quebibyte,
 }
 
 #[derive(Deserialize)]
@@ -36,6 +44,7 @@ pub enum UnitDef {
 pub enum BaseDef {
     Base2,
     Base10,
+    Jedec,
 }
 
 #[derive(Deserialize)]
@@ -64,28 +73,36 @@ fuzz_target!(|data: &[u8]| {
         println!("{:?}", data);
         let Data{number, unit, base, style} = data;
         let size = match unit {
-            Unit::Byte => Size::Bytes(number),
-            Unit::Kibibyte => Size::Kibibytes(number),
-            Unit::Kilobyte => Size::Kilobytes(number),
-            Unit::Mebibyte => Size::Mebibytes(number),
-            Unit::Megabyte => Size::Megabytes(number),
-            Unit::Gibibyte => Size::Gibibytes(number),
-            Unit::Gigabyte => Size::Gigabytes(number),
-            Unit::Tebibyte => Size::Tebibytes(number),
-            Unit::Terabyte => Size::Terabytes(number),
-            Unit::Pebibyte => Size::Pebibytes(number),
-            Unit::Petabyte => Size::Petabytes(number),
-            Unit::Exbibyte => Size::Exbibytes(number),
-            Unit::Exabyte => Size::Exabytes(number),
+            Unit::Byte => TypedSize::Bytes(number),
+            Unit::Kibibyte => TypedSize::Kibibytes(number),
+            Unit::Kilobyte => TypedSize::Kilobytes(number),
+            Unit::Mebibyte => TypedSize::Mebibytes(number),
+            Unit::Megabyte => TypedSize::Megabytes(number),
+            Unit::Gibibyte => TypedSize::Gibibytes(number),
+            Unit::Gigabyte => TypedSize::Gigabytes(number),
+            Unit::Tebibyte => TypedSize::Tebibytes(number),
+            Unit::Terabyte => TypedSize::Terabytes(number),
+            Unit::Pebibyte => TypedSize::Pebibytes(number),
+            Unit::Petabyte => TypedSize::Petabytes(number),
+            Unit::Exbibyte => TypedSize::Exbibytes(number),
+            Unit::Exabyte => TypedSize::Exabytes(number),
+            Unit::Zettabyte => TypedSize::Zettabytes(number),
+            Unit::Zebibyte => TypedSize::Zebibytes(number),
+            Unit::Yottabyte => TypedSize::Yottabytes(number),
+            Unit::Yobibyte => TypedSize::Yobibytes(number),
+            Unit::Ronnabyte => TypedSize::Ronnabytes(number),
+            Unit::Robibyte => TypedSize::Robibytes(number),
+            Unit::Quettabyte => TypedSize::Quettabytes(number),
+            Unit::Quebibyte => TypedSize::Quebibytes(number),
         };
-        let abs = number.abs() as u64;
-        println!("assert_eq!({:?}, Size::{:?}s::<i64>({:?}).bytes())", abs, unit, number);
+        let abs = number.abs() as u128;
+        println!("assert_eq!({:?}, TypedSize::{:?}s::<i64>({:?}).bytes())", abs, unit, number);
         println!("{:?} == {:?}", abs, size.bytes());
         assert_eq!(abs, size.bytes());
-        let s = size.to_string(base, style);
+        let s = size.to_string_with(base, style);
         println!("{:?} => {:?}", size, s);
-        println!("assert_eq!(Size::<i64>::from_str({:?}).unwrap(), Size::{:?}s::<i64>({:?}))", s, unit, number);
-        let s = Size::<i64>::from_str(&s).unwrap();
+        println!("assert_eq!(TypedSize::<i64>::from_str({:?}).unwrap(), TypedSize::{:?}s::<i64>({:?}))", s, unit, number);
+        let s = TypedSize::<i64>::from_str(&s).unwrap();
         println!("{:?} == {:?}", size, s);
         let size = format!("{:.2e}", size.bytes() as f64);
         let s = format!("{:.2e}", s.bytes() as f64);
@@ -7,7 +7,9 @@ use size::Size;
 use std::str::FromStr;
 
 fuzz_target!(|data: &[u8]| {
+    let from_bytes = Size::parse_bytes(data);
     if let Ok(s) = std::str::from_utf8(data) {
-        let _ = Size::<f64>::from_str(s);
+        let from_str = Size::from_str(s);
+        assert_eq!(format!("{:?}", from_str), format!("{:?}", from_bytes));
     }
 });
@@ -0,0 +1,139 @@
+//! Signed differences between sizes, e.g. how much a directory grew or shrank.
+
+use crate::{Base, ParseError, Size, SizeFormatter, SizeParser, DEFAULT_BASE};
+use core::ops::{Add, Neg, Sub};
+use std::fmt;
+use std::str::FromStr;
+
+/// The signed difference between two sizes, counted in bytes. Subtracting one `Size` from
+/// another returns a `SizeDelta`, and adding a `SizeDelta` to a `Size` returns a `Size`
+/// again.
+///
+/// ```
+/// use size::{Size, SizeDelta};
+///
+/// let delta = Size::from_mib(2) - Size::from_kib(3000);
+/// assert_eq!(delta.bytes(), -952 * 1024);
+/// assert_eq!(delta.to_string(), "-952 KiB");
+/// assert_eq!((-delta).to_string(), "+952 KiB");
+/// assert_eq!(Size::from_kib(3000) + delta, Size::from_mib(2));
+/// assert_eq!("-952 KiB".parse::<SizeDelta>().unwrap(), delta);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct SizeDelta {
+    bytes: i128,
+}
+
+impl SizeDelta {
+    /// Creates a delta of the given number of bytes.
+    pub const fn from_bytes(bytes: i128) -> Self {
+        SizeDelta { bytes }
+    }
+
+    /// Returns the number of bytes in the delta, negative if it is a decrease.
+    pub const fn bytes(&self) -> i128 {
+        self.bytes
+    }
+
+    /// Returns the delta without its sign, as a size. Unlike negating the delta, this can't
+    /// overflow, as a `Size` also holds the 2^127 bytes of the largest decrease.
+    pub const fn abs(&self) -> Size {
+        Size::from_bytes(self.bytes.unsigned_abs())
+    }
+
+    /// Returns whether the delta is a decrease.
+    pub const fn is_negative(&self) -> bool {
+        self.bytes < 0
+    }
+
+    /// Returns the sum of the two deltas, or `None` if it doesn't fit in an `i128`.
+    pub const fn checked_add(self, other: SizeDelta) -> Option<SizeDelta> {
+        match self.bytes.checked_add(other.bytes) {
+            Some(bytes) => Some(SizeDelta::from_bytes(bytes)),
+            None => None,
+        }
+    }
+
+    /// Returns the sum of the two deltas, clamped to the range of an `i128`.
+    pub const fn saturating_add(self, other: SizeDelta) -> SizeDelta {
+        SizeDelta::from_bytes(self.bytes.saturating_add(other.bytes))
+    }
+
+    /// Returns the difference of the two deltas, or `None` if it doesn't fit in an `i128`.
+    pub const fn checked_sub(self, other: SizeDelta) -> Option<SizeDelta> {
+        match self.bytes.checked_sub(other.bytes) {
+            Some(bytes) => Some(SizeDelta::from_bytes(bytes)),
+            None => None,
+        }
+    }
+
+    /// Returns the difference of the two deltas, clamped to the range of an `i128`.
+    pub const fn saturating_sub(self, other: SizeDelta) -> SizeDelta {
+        SizeDelta::from_bytes(self.bytes.saturating_sub(other.bytes))
+    }
+}
+
+impl fmt::Debug for SizeDelta {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{:+} bytes", self.bytes)
+    }
+}
+
+impl fmt::Display for SizeDelta {
+    /// Formats the delta with an explicit sign, in the default base and style. The format
+    /// specifiers are honored like for `Size`, and the alternate flag (`{:#}`) switches to
+    /// [`Base::Base10`] units.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let base = match fmt.alternate() {
+            true => Base::Base10,
+            false => DEFAULT_BASE,
+        };
+        SizeFormatter::new()
+            .with_base(base)
+            .display_delta(*self)
+            .fmt(fmt)
+    }
+}
+
+impl FromStr for SizeDelta {
+    type Err = ParseError;
+
+    /// Parses a size with an optional sign, e.g. "-5 MB" or "+1.20 MiB". This is a
+    /// shorthand for [`SizeParser::parse_delta()`] with the default options.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SizeParser::new().parse_delta(s)
+    }
+}
+
+/// Negates a delta, panicking on the largest decrease of 2^127 bytes, which has no
+/// positive counterpart in an `i128`. Use [`SizeDelta::abs()`] for the magnitude instead.
+impl Neg for SizeDelta {
+    type Output = SizeDelta;
+
+    fn neg(self) -> Self::Output {
+        let bytes = self.bytes.checked_neg();
+        SizeDelta::from_bytes(bytes.expect("attempt to negate with overflow"))
+    }
+}
+
+/// Adds two deltas, panicking if the sum doesn't fit in an `i128`. Use
+/// [`SizeDelta::checked_add()`] or [`SizeDelta::saturating_add()`] to handle that instead.
+impl Add for SizeDelta {
+    type Output = SizeDelta;
+
+    fn add(self, other: SizeDelta) -> Self::Output {
+        let sum = self.checked_add(other);
+        sum.expect("attempt to add with overflow")
+    }
+}
+
+/// Subtracts two deltas, panicking if the difference doesn't fit in an `i128`. Use
+/// [`SizeDelta::checked_sub()`] or [`SizeDelta::saturating_sub()`] to handle that instead.
+impl Sub for SizeDelta {
+    type Output = SizeDelta;
+
+    fn sub(self, other: SizeDelta) -> Self::Output {
+        let difference = self.checked_sub(other);
+        difference.expect("attempt to subtract with overflow")
+    }
+}
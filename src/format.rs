@@ -0,0 +1,1054 @@
+//! Human-readable formatting of `Size` values.
+//! A [`SizeFormatter`] holds the formatting options (the table of rules picking the unit
+//! and number of decimals, style, unit names, precision, rounding mode, locale and the
+//! separator between the number and its unit) and can be reused across any number of sizes.
+//! [`SizeFormatter::display()`] takes a size and returns an adapter that implements
+//! `Display`, so sizes can be written to any `fmt::Write` or `io::Write` without first
+//! being collected into a `String`.
+
+use crate::Unit::*;
+use crate::{Base, Locale, Size, SizeDelta, Style, Unit, UnitNames, DEFAULT_BASE, DEFAULT_STYLE};
+use crate::{EXABYTE, EXBIBYTE, GIBIBYTE, GIGABYTE, KIBIBYTE, KILOBYTE};
+use crate::{MEBIBYTE, MEGABYTE, PEBIBYTE, PETABYTE, QUEBIBYTE, QUETTABYTE, ROBIBYTE};
+use crate::{RONNABYTE, TEBIBYTE, TERABYTE, YOBIBYTE, YOTTABYTE, ZEBIBYTE, ZETTABYTE};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{self, Write};
+
+/// The most digits a rounded number is computed with, as many as any `u128` has.
+const MAX_DIGITS: usize = 38;
+
+/// A reusable set of options for formatting sizes in a human-readable form.
+///
+/// ```
+/// use size::{Base, Precision, Size, SizeFormatter, Style};
+///
+/// let formatter = SizeFormatter::new()
+///     .with_base(Base::Base10)
+///     .with_style(Style::Full)
+///     .with_precision(Precision::Decimals(1));
+/// assert_eq!(formatter.display(Size::from_kb(42)).to_string(), "42.0 Kilobytes");
+/// assert_eq!(format!("{}", formatter.display(Size::from_bytes(1))), "1 Byte");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SizeFormatter<'a> {
+    rules: FormatRules<'a>,
+    style: Style,
+    unit_names: &'static UnitNames,
+    precision: Precision,
+    trailing_zeros: bool,
+    scientific: Option<u32>,
+    rounding: RoundingMode,
+    locale: Locale,
+    separator: &'static str,
+}
+
+impl Default for SizeFormatter<'static> {
+    fn default() -> Self {
+        SizeFormatter::new()
+    }
+}
+
+impl SizeFormatter<'static> {
+    /// Creates a formatter with the same options used by `Display` for `Size`.
+    pub fn new() -> Self {
+        SizeFormatter {
+            rules: FormatRules::for_base(&DEFAULT_BASE),
+            style: DEFAULT_STYLE,
+            unit_names: UnitNames::EN,
+            precision: Precision::Auto,
+            trailing_zeros: true,
+            scientific: None,
+            rounding: RoundingMode::HalfEven,
+            locale: Locale::new(),
+            separator: " ",
+        }
+    }
+}
+
+impl<'a> SizeFormatter<'a> {
+    /// Sets the base (and therefore the family of units) used for output, replacing any
+    /// rules set with [`SizeFormatter::with_rules()`].
+    pub fn with_base(self, base: Base) -> Self {
+        SizeFormatter {
+            rules: FormatRules::for_base(&base),
+            ..self
+        }
+    }
+
+    /// Formats sizes with a custom table of rules, in place of the table for the base.
+    pub fn with_rules<'b>(self, rules: FormatRules<'b>) -> SizeFormatter<'b> {
+        SizeFormatter {
+            rules,
+            style: self.style,
+            unit_names: self.unit_names,
+            precision: self.precision,
+            trailing_zeros: self.trailing_zeros,
+            scientific: self.scientific,
+            rounding: self.rounding,
+            locale: self.locale,
+            separator: self.separator,
+        }
+    }
+
+    /// Sets the style the unit is printed in.
+    pub fn with_style(self, style: Style) -> Self {
+        SizeFormatter { style, ..self }
+    }
+
+    /// Prints units with the names and symbols of another language, in the style set with
+    /// [`SizeFormatter::with_style()`].
+    ///
+    /// ```
+    /// use size::{Locale, Size, SizeFormatter, Style, UnitNames};
+    ///
+    /// let formatter = SizeFormatter::new()
+    ///     .with_unit_names(UnitNames::RU)
+    ///     .with_locale(Locale::DE.with_grouping('\u{a0}', 3));
+    /// assert_eq!(formatter.display(Size::from_mib(3)).to_string(), "3,00 МиБ");
+    /// let formatter = formatter.with_style(Style::FullLowerCase);
+    /// assert_eq!(formatter.display(Size::from_bytes(512)).to_string(), "512 байт");
+    /// ```
+    pub fn with_unit_names(self, unit_names: &'static UnitNames) -> Self {
+        SizeFormatter { unit_names, ..self }
+    }
+
+    /// Sets how many digits are printed, [`Precision::Auto`] by default. Sizes printed in
+    /// bytes are always exact and never have decimal places.
+    pub fn with_precision(self, precision: Precision) -> Self {
+        SizeFormatter { precision, ..self }
+    }
+
+    /// Sets whether zeros at the end of the decimal places are printed (the default), or
+    /// trimmed so that e.g. "2.00 MiB" is printed as "2 MiB" and "2.50 MiB" as "2.5 MiB".
+    pub fn with_trailing_zeros(self, trailing_zeros: bool) -> Self {
+        SizeFormatter {
+            trailing_zeros,
+            ..self
+        }
+    }
+
+    /// Prints numbers in scientific notation (e.g. "1.50e6 MB") when they are `10^exponent`
+    /// or more in the unit picked by the rules, or non-zero and less than `10^-exponent`.
+    /// With [`Precision::Auto`] and [`Precision::Decimals`] the precision applies to the
+    /// decimal places of the mantissa.
+    pub fn with_scientific_notation(self, exponent: u32) -> Self {
+        SizeFormatter {
+            scientific: Some(exponent),
+            ..self
+        }
+    }
+
+    /// Sets how numbers are rounded to the number of decimal places being printed,
+    /// [`RoundingMode::HalfEven`] by default. The unit is picked after rounding, so
+    /// rounding up can carry the size over into the next unit.
+    pub fn with_rounding(self, rounding: RoundingMode) -> Self {
+        SizeFormatter { rounding, ..self }
+    }
+
+    /// Sets the text written between the number and the unit, a single space by default.
+    pub fn with_separator(self, separator: &'static str) -> Self {
+        SizeFormatter { separator, ..self }
+    }
+
+    /// Writes numbers with the decimal separator and digit grouping of `locale`, and
+    /// separates them from the unit with its unit separator, which
+    /// [`SizeFormatter::with_separator()`] can still override afterwards.
+    ///
+    /// ```
+    /// use size::{Base, Locale, Size, SizeFormatter};
+    ///
+    /// let formatter = SizeFormatter::new().with_locale(Locale::EN).with_base(Base::Base10);
+    /// assert_eq!(formatter.display(Size::from_kb(1500)).to_string(), "1.50 MB");
+    /// assert_eq!(formatter.display(Size::from_bytes(999)).to_string(), "999 bytes");
+    ///
+    /// let formatter = SizeFormatter::new().with_locale(Locale::FR);
+    /// assert_eq!(formatter.display(Size::from_kib(1500)).to_string(), "1,46\u{a0}MiB");
+    /// ```
+    pub fn with_locale(self, locale: Locale) -> Self {
+        SizeFormatter {
+            locale,
+            separator: locale.unit_separator(),
+            ..self
+        }
+    }
+
+    /// Returns an object implementing `Display` that formats `size` with
+    /// these options. No allocations are made when the returned value is formatted.
+    ///
+    /// A precision given in the format string (e.g. `{:.1}`) is treated as
+    /// [`Precision::Decimals`] and takes priority over the one configured
+    /// here, and width, fill and alignment are applied to the whole output.
+    pub fn display(&self, size: Size) -> SizeDisplay<'_> {
+        SizeDisplay {
+            formatter: self,
+            size,
+        }
+    }
+
+    /// Like [`SizeFormatter::display()`], but for a signed [`SizeDelta`], which is printed
+    /// with an explicit sign (e.g. "+1.20 MiB" or "-512 KiB") unless it is zero. The
+    /// rounding mode applies to the signed value, so [`RoundingMode::Floor`] rounds
+    /// negative deltas away from zero.
+    pub fn display_delta(&self, delta: SizeDelta) -> SizeDeltaDisplay<'_> {
+        let sign = match delta.bytes().cmp(&0) {
+            Ordering::Less => "-",
+            Ordering::Equal => "",
+            Ordering::Greater => "+",
+        };
+        SizeDeltaDisplay {
+            formatter: self,
+            sign,
+            bytes: delta.bytes().unsigned_abs(),
+        }
+    }
+
+    /// Like [`SizeFormatter::display_delta()`], but for minus `bytes`, which may be more
+    /// than a `SizeDelta` holds.
+    pub(crate) fn display_negative(&self, bytes: u128) -> SizeDeltaDisplay<'_> {
+        SizeDeltaDisplay {
+            formatter: self,
+            sign: "-",
+            bytes,
+        }
+    }
+
+    /// Formats `bytes`, rounding its magnitude away from zero instead of towards it (and
+    /// vice versa) when it's `negative`, so that e.g. [`RoundingMode::Floor`] still rounds
+    /// towards negative infinity.
+    fn format(
+        &self,
+        fmt: &mut dyn Write,
+        bytes: u128,
+        precision: Option<usize>,
+        negative: bool,
+    ) -> fmt::Result {
+        let rounding = match negative {
+            true => self.rounding.mirrored(),
+            false => self.rounding,
+        };
+        let precision = match precision {
+            Some(decimals) => Precision::Decimals(decimals),
+            None => self.precision,
+        };
+
+        let (rule, number) = self.pick_rule(bytes, precision, rounding);
+        let write_number = |fmt: &mut dyn Write| match rule.unit {
+            // Byte counts are integral, so there are never any decimal places to show
+            Unit::Byte => write!(fmt, "{}", bytes),
+            _ => number.write(fmt),
+        };
+        match self.locale.is_plain() {
+            true => write_number(fmt)?,
+            false => {
+                let mut localized = Localized::new(fmt, &self.locale);
+                write_number(&mut localized)?;
+                localized.flush()?;
+            }
+        }
+        fmt.write_str(self.separator)?;
+        let unit = self.rules.label(rule.unit);
+        let number = match rule.unit {
+            Unit::Byte => (bytes, false),
+            _ => number.whole_part(),
+        };
+        self.unit_names.format(fmt, unit, number, &self.style)
+    }
+
+    /// Returns the unit `bytes` is printed in with these options.
+    pub(crate) fn unit(&self, bytes: u128) -> Unit {
+        self.pick_rule(bytes, self.precision, self.rounding).0.unit
+    }
+
+    /// Returns the rule `bytes` is printed with, along with the number it is rounded to.
+    fn pick_rule(
+        &self,
+        bytes: u128,
+        precision: Precision,
+        rounding: RoundingMode,
+    ) -> (&FormatRule, Rounded) {
+        let mut index = self.rules.position(bytes);
+        loop {
+            let rule = &self.rules.rules[index];
+            let divisor = rule.unit.bytes_per_unit();
+            let decimals = rule.decimals;
+            let number = self.round(bytes, divisor, precision, decimals, rounding);
+
+            // The unit is picked by the rounded value, so that e.g. 1023.9 KiB is printed
+            // as 1.00 MiB rather than as 1024 KiB. Likewise, a number rounded to fewer
+            // significant figures than its four or more digits moves on to the next unit,
+            // so that 999.5 MiB is printed as 0.98 GiB rather than as 1000 MiB.
+            let carried = match precision {
+                // Byte counts are printed as they are, without rounding
+                _ if rule.unit == Unit::Byte => false,
+                Precision::SignificantFigures(digits) => 10u128
+                    .checked_pow(digits.clamp(3, MAX_DIGITS) as u32)
+                    .is_some_and(|limit| number.reaches(limit, 1)),
+                _ => false,
+            };
+            match self.rules.rules.get(index + 1) {
+                Some(_) if carried || number.reaches(rule.less_than, divisor) => index += 1,
+                _ => return (rule, number),
+            }
+        }
+    }
+
+    /// Rounds `bytes / divisor` for printing, given the decimal places of the rule. The
+    /// digits are computed exactly, with integer arithmetic only, up to 38 significant
+    /// digits and 38 decimal places (as many as a `u128` holds), and any further decimal
+    /// places asked for are zeros.
+    fn round(
+        &self,
+        bytes: u128,
+        divisor: u128,
+        precision: Precision,
+        decimals: usize,
+        rounding: RoundingMode,
+    ) -> Rounded {
+        let exponent = match (precision, self.scientific) {
+            (Precision::SignificantFigures(_), _) | (_, Some(_)) => exact_magnitude(bytes, divisor),
+            _ => 0,
+        };
+        let scientific = match self.scientific {
+            Some(limit) => {
+                let limit = i64::from(limit);
+                bytes != 0 && (i64::from(exponent) >= limit || i64::from(exponent) < -limit)
+            }
+            None => false,
+        };
+        // The power of ten the number is printed times, in scientific notation
+        let shift = match scientific {
+            true => exponent,
+            false => 0,
+        };
+
+        let (mut places, mut padding) = match precision {
+            Precision::Auto => (decimals.min(MAX_DIGITS) as i32, decimals),
+            Precision::Decimals(decimals) => (decimals.min(MAX_DIGITS) as i32, decimals),
+            Precision::SignificantFigures(digits) => {
+                let digits = digits.clamp(1, MAX_DIGITS) as i32;
+                (digits - 1 - (exponent - shift), 0)
+            }
+        };
+        // Past 38 decimal places, a power of ten doesn't fit in a `u128`, and a mantissa
+        // can only have 37 of them
+        let limit = MAX_DIGITS as i32 - scientific as i32;
+        places = places.min(limit).min(MAX_DIGITS as i32 + shift);
+        let mut digits = match round_scaled(bytes, divisor, places - shift, rounding) {
+            Some(digits) => digits,
+            None => {
+                // Past 38 digits, the number doesn't fit in a `u128`
+                let magnitude = exact_magnitude(bytes, divisor) - shift;
+                places = places.min(MAX_DIGITS as i32 - 1 - magnitude);
+                round_scaled(bytes, divisor, places - shift, rounding).unwrap_or(u128::MAX)
+            }
+        };
+        let mut exponent = match scientific {
+            true => Some(exponent),
+            false => None,
+        };
+
+        if places < 0 {
+            // Only ever the case for a number of bytes that is at least `10^-places` times
+            // the divisor, so this can only saturate in a unit of a single byte, which is
+            // printed as the number of bytes anyway
+            digits = digits.saturating_mul(10u128.pow(places.unsigned_abs()));
+            places = 0;
+        }
+        let mut places = places as usize;
+        padding = padding.saturating_sub(places);
+
+        match (precision, exponent) {
+            // Rounding up added a digit, e.g. 9.996e3 to 10.00e3
+            (_, Some(power)) if digits >= 10u128.pow(places as u32 + 1) => {
+                digits /= 10;
+                exponent = Some(power + 1);
+            }
+            // Rounding up added a digit, e.g. 9.996 to 10.00
+            (Precision::SignificantFigures(wanted), None)
+                if places > 0 && digits >= 10u128.pow(wanted.clamp(1, MAX_DIGITS) as u32) =>
+            {
+                digits /= 10;
+                places -= 1;
+            }
+            _ => (),
+        }
+        if !self.trailing_zeros {
+            padding = 0;
+            while places > 0 && digits % 10 == 0 {
+                digits /= 10;
+                places -= 1;
+            }
+        }
+        Rounded {
+            digits,
+            decimals: places,
+            padding,
+            exponent,
+        }
+    }
+}
+
+/// How many digits are printed when formatting a size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    /// The number of decimal places picked by the formatting rules for the magnitude of
+    /// the size, e.g. "2.00 MiB", "20.0 MiB" and "200 MiB" with the built-in rules.
+    Auto,
+    /// A fixed number of decimal places. They are computed exactly up to the 38th decimal
+    /// place or 38th significant digit, whichever comes first, and any further ones are
+    /// printed as zeros.
+    Decimals(usize),
+    /// A fixed number of significant figures, e.g. "2.0 MiB", "20 MiB" and "200 MiB" with
+    /// two significant figures. Zero is taken as one, and more than 38 as 38. A number that would need four or more
+    /// digits is printed in the next unit instead, e.g. "0.98 GiB" rather than "1000 MiB"
+    /// for 999.5 MiB.
+    SignificantFigures(usize),
+}
+
+/// A number rounded for printing: `digits / 10^decimals`, followed by `padding` more
+/// zeros in its decimal places, and times `10^exponent` if it is printed in scientific
+/// notation.
+struct Rounded {
+    digits: u128,
+    decimals: usize,
+    padding: usize,
+    exponent: Option<i32>,
+}
+
+impl Rounded {
+    /// Returns whether the number is at least `bytes / divisor`.
+    fn reaches(&self, bytes: u128, divisor: u128) -> bool {
+        let places = self.decimals as i32 - self.exponent.unwrap_or(0);
+        match u32::try_from(places) {
+            Ok(places) => match 10u128.checked_pow(places) {
+                Some(scale) => wide_mul(self.digits, divisor) >= wide_mul(bytes, scale),
+                None => bytes == 0,
+            },
+            // Past `u128::MAX`, the number is larger than any size
+            Err(_) => match 10u128
+                .checked_pow(places.unsigned_abs())
+                .and_then(|scale| self.digits.checked_mul(scale))
+            {
+                Some(value) => wide_mul(value, divisor) >= (0, bytes),
+                None => true,
+            },
+        }
+    }
+
+    /// Returns the whole part of the number as it is written, and whether it is written
+    /// with decimal places or an exponent.
+    fn whole_part(&self) -> (u128, bool) {
+        let whole = self.digits / 10u128.pow(self.decimals as u32);
+        let fraction = self.decimals > 0 || self.padding > 0 || self.exponent.is_some();
+        (whole, fraction)
+    }
+
+    fn write(&self, fmt: &mut dyn Write) -> fmt::Result {
+        let (digits, decimals) = (self.digits, self.decimals);
+        match u64::try_from(digits) {
+            Ok(digits) if decimals <= 19 => write_fixed(fmt, digits, decimals)?,
+            _ => {
+                let scale = 10u128.pow(decimals as u32);
+                match decimals {
+                    0 => write!(fmt, "{}", digits)?,
+                    _ => write!(fmt, "{}.{:02$}", digits / scale, digits % scale, decimals)?,
+                }
+            }
+        }
+        if self.padding > 0 {
+            if decimals == 0 {
+                fmt.write_char('.')?;
+            }
+            for _ in 0..self.padding {
+                fmt.write_char('0')?;
+            }
+        }
+        match self.exponent {
+            Some(exponent) => write!(fmt, "e{}", exponent),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes `digits / 10^decimals` with exactly `decimals` (at most 19) decimal places.
+/// This is a lot faster than going through `write!()`, and than dividing `u128`s.
+fn write_fixed(fmt: &mut dyn Write, mut digits: u64, decimals: usize) -> fmt::Result {
+    // Room for the 20 digits of u64::MAX, a decimal point and a leading zero
+    let mut buffer = [b'0'; 22];
+    let mut start = buffer.len();
+    for _ in 0..decimals {
+        start -= 1;
+        buffer[start] = b'0' + (digits % 10) as u8;
+        digits /= 10;
+    }
+    if decimals > 0 {
+        start -= 1;
+        buffer[start] = b'.';
+    }
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (digits % 10) as u8;
+        digits /= 10;
+        if digits == 0 {
+            break;
+        }
+    }
+    let text = std::str::from_utf8(&buffer[start..]).map_err(|_| fmt::Error)?;
+    fmt.write_str(text)
+}
+
+/// A `fmt::Write` adapter that rewrites a number as it goes through, from the way Rust
+/// formats it to the way `locale` does: the decimal point is swapped for the locale's
+/// separator and the digits before it are grouped.
+struct Localized<'a> {
+    out: &'a mut dyn Write,
+    locale: &'a Locale,
+    /// The digits before the decimal point, which can't be grouped until all of them are
+    /// known. A size has at most 39 of them, as many as `u128::MAX`.
+    whole: [u8; 39],
+    len: usize,
+    flushed: bool,
+}
+
+impl<'a> Localized<'a> {
+    fn new(out: &'a mut dyn Write, locale: &'a Locale) -> Self {
+        Localized {
+            out,
+            locale,
+            whole: [0; 39],
+            len: 0,
+            flushed: false,
+        }
+    }
+
+    /// Writes out the digits before the decimal point, if they haven't been already.
+    fn flush(&mut self) -> fmt::Result {
+        if self.flushed {
+            return Ok(());
+        }
+        self.flushed = true;
+        for (i, &digit) in self.whole[..self.len].iter().enumerate() {
+            match self.locale.grouping() {
+                Some((separator, size)) if i > 0 && (self.len - i).is_multiple_of(size) => {
+                    self.out.write_char(separator)?
+                }
+                _ => (),
+            }
+            self.out.write_char(digit as char)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Localized<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match c {
+                '0'..='9' if !self.flushed && self.len < self.whole.len() => {
+                    self.whole[self.len] = c as u8;
+                    self.len += 1;
+                }
+                _ => {
+                    self.flush()?;
+                    match c {
+                        '.' => self.out.write_char(self.locale.decimal_separator())?,
+                        c => self.out.write_char(c)?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the 256-bit product of `a` and `b`, as its high and low halves.
+fn wide_mul(a: u128, b: u128) -> (u128, u128) {
+    const LOW: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & LOW);
+    let (b_high, b_low) = (b >> 64, b & LOW);
+    // None of the partial sums can overflow, as (2^64 - 1)^2 + 2 * (2^64 - 1) < 2^128
+    let low = a_low * b_low;
+    let middle = a_high * b_low + (low >> 64);
+    let middle = (middle >> 64, a_low * b_high + (middle & LOW));
+    let high = a_high * b_high + middle.0 + (middle.1 >> 64);
+    (high, middle.1 << 64 | low & LOW)
+}
+
+/// Returns the quotient and remainder of the 256-bit `numerator` divided by `denominator`,
+/// or `None` if the quotient doesn't fit in a `u128`.
+fn wide_div((high, low): (u128, u128), denominator: u128) -> Option<(u128, u128)> {
+    if high == 0 {
+        // 128-bit division is a lot slower than 64-bit division, and rarely needed
+        return Some(match (u64::try_from(low), u64::try_from(denominator)) {
+            (Ok(low), Ok(denominator)) => {
+                ((low / denominator) as u128, (low % denominator) as u128)
+            }
+            _ => (low / denominator, low % denominator),
+        });
+    }
+    if high >= denominator {
+        return None;
+    }
+
+    // Long division of the low half, one bit at a time
+    let (mut quotient, mut remainder) = (0, high);
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = remainder << 1 | (low >> bit) & 1;
+        quotient <<= 1;
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1;
+        }
+    }
+    Some((quotient, remainder))
+}
+
+/// Returns `bytes / divisor` times `10^places`, rounded to an integer, or `None` if that
+/// doesn't fit in a `u128`. `places` is at most 38, and at least minus the power of ten
+/// of the most significant digit of `bytes / divisor`.
+fn round_scaled(bytes: u128, divisor: u128, places: i32, rounding: RoundingMode) -> Option<u128> {
+    let (numerator, denominator) = match u32::try_from(places) {
+        Ok(places) => (wide_mul(bytes, 10u128.pow(places)), divisor),
+        // `bytes` is at least `divisor * 10^-places`, so this can't overflow
+        Err(_) => ((0, bytes), divisor * 10u128.pow(places.unsigned_abs())),
+    };
+    let (quotient, remainder) = wide_div(numerator, denominator)?;
+
+    // `remainder` is compared to `denominator - remainder` rather than doubled, so it
+    // can't overflow
+    let mut fraction = remainder.cmp(&(denominator - remainder));
+    if rounding == RoundingMode::HalfEven && near_tie(quotient, remainder, denominator) {
+        fraction = float_fraction(bytes, divisor, places, quotient);
+    }
+    quotient.checked_add(rounding.rounds_up(quotient % 2 == 1, fraction, remainder == 0) as u128)
+}
+
+/// Returns whether `quotient + remainder / denominator` is so close to a tie that the
+/// `f64` closest to it may be on the other side of it. The three roundings on the way to
+/// that `f64` (of the number of bytes, the divisor and the quotient) are off by less than
+/// `2^-51` times the number, and the distance to the tie is `|2 * remainder -
+/// denominator| / (2 * denominator)`. Numbers of more than 15 digits are always rounded
+/// exactly, as the `f64` isn't precise enough to tell their last digit anymore.
+fn near_tie(quotient: u128, remainder: u128, denominator: u128) -> bool {
+    let distance = remainder.abs_diff(denominator - remainder);
+    quotient < 1 << 50
+        && match (quotient + 1).checked_mul(denominator) {
+            Some(bound) => distance <= bound >> 49,
+            None => true,
+        }
+}
+
+/// Returns how the `f64` closest to `bytes / divisor`, times `10^places`, compares to
+/// `quotient + 1/2`, exactly. `places` is in the same range as for [`round_scaled()`].
+fn float_fraction(bytes: u128, divisor: u128, places: i32, quotient: u128) -> Ordering {
+    // The value is `mantissa * 2^exponent`, and is never subnormal, as it is at least the
+    // reciprocal of the largest unit
+    let bits = (bytes as f64 / divisor as f64).to_bits();
+    let mantissa = (bits & ((1 << 52) - 1) | 1 << 52) as u128;
+    let exponent = (bits >> 52) as i32 - 1075;
+
+    // Both sides are doubled, to compare whole numbers
+    let (value, tie) = match u32::try_from(places) {
+        Ok(places) => {
+            let (high, low) = wide_mul(quotient, 2);
+            (wide_mul(2 * mantissa, 10u128.pow(places)), (high, low | 1))
+        }
+        Err(_) => {
+            let scale = 10u128.pow(places.unsigned_abs());
+            ((0, 2 * mantissa), wide_mul(2 * quotient + 1, scale))
+        }
+    };
+    compare_shifted(value, exponent, tie)
+}
+
+/// Compares the 256-bit `a` times `2^shift` to the 256-bit `b`.
+fn compare_shifted(a: (u128, u128), shift: i32, b: (u128, u128)) -> Ordering {
+    let bits = |(high, low): (u128, u128)| match high {
+        0 => 128 - low.leading_zeros() as i32,
+        _ => 256 - high.leading_zeros() as i32,
+    };
+    if a == (0, 0) || b == (0, 0) {
+        return a.cmp(&b);
+    }
+    // Only numbers of the same length are shifted, so that nothing is shifted out
+    match (bits(a) + shift).cmp(&bits(b)) {
+        Ordering::Equal if shift >= 0 => shift_left(a, shift as u32).cmp(&b),
+        Ordering::Equal => a.cmp(&shift_left(b, shift.unsigned_abs())),
+        ordering => ordering,
+    }
+}
+
+/// Shifts the 256-bit `value` left by `shift` bits, less than 256.
+fn shift_left((high, low): (u128, u128), shift: u32) -> (u128, u128) {
+    match shift {
+        0 => (high, low),
+        1..=127 => (high << shift | low >> (128 - shift), low << shift),
+        _ => (low << (shift - 128), 0),
+    }
+}
+
+/// Returns the power of ten of the most significant digit of `numerator / denominator`.
+fn exact_magnitude(numerator: u128, denominator: u128) -> i32 {
+    let mut exponent = 0;
+    if numerator == 0 {
+        return exponent;
+    }
+
+    if numerator >= denominator {
+        let mut quotient = numerator / denominator;
+        while quotient >= 10 {
+            quotient /= 10;
+            exponent += 1;
+        }
+    } else {
+        // The denominator is the size of a unit, so this can't overflow
+        let mut numerator = numerator;
+        while numerator < denominator {
+            numerator *= 10;
+            exponent -= 1;
+        }
+    }
+    exponent
+}
+
+/// How a number is rounded to the number of decimal places being printed.
+///
+/// By default, sizes are rounded exactly like older versions did, which divided as `f64`
+/// and let the formatting of the `f64` round it: 1015 bytes are exactly 1.015 KB, but the
+/// `f64` closest to that is slightly less, so they are printed as "1.01 KB" with
+/// [`RoundingMode::HalfEven`]. [`RoundingMode::HalfEvenExact`] and the other modes round
+/// the exact number of bytes instead.
+///
+/// ```
+/// use size::{Base, RoundingMode, Size, SizeFormatter};
+///
+/// let formatter = SizeFormatter::new().with_base(Base::Base10);
+/// assert_eq!(formatter.display(Size::from_bytes(1015)).to_string(), "1.01 KB");
+/// assert_eq!(formatter.display(Size::from_bytes(1025)).to_string(), "1.02 KB");
+/// let formatter = formatter.with_rounding(RoundingMode::HalfEvenExact);
+/// assert_eq!(formatter.display(Size::from_bytes(1015)).to_string(), "1.02 KB");
+/// let formatter = formatter.with_rounding(RoundingMode::HalfUp);
+/// assert_eq!(formatter.display(Size::from_bytes(1025)).to_string(), "1.03 KB");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round to the nearest value, with ties rounded away from zero.
+    HalfUp,
+    /// Round to the nearest value, with ties rounded to an even last digit. Like the
+    /// formatting of an `f64`, which older versions used, a size close to a tie is
+    /// rounded as the `f64` closest to it when formatting, unless it is printed with more
+    /// than 15 digits.
+    HalfEven,
+    /// Like [`RoundingMode::HalfEven`], but ties are decided on the exact number of bytes.
+    HalfEvenExact,
+    /// Round towards negative infinity, never over-reporting a size.
+    Floor,
+    /// Round towards positive infinity, never under-reporting a size (like `du -h`).
+    Ceil,
+    /// Drop the digits that aren't printed, rounding towards zero.
+    Truncate,
+}
+
+impl RoundingMode {
+    /// Returns whether a (non-negative) number is rounded up to the next integer, given
+    /// whether its integer part is odd, how its fractional part compares to one half and
+    /// whether the fractional part is zero.
+    pub(crate) fn rounds_up(self, odd: bool, fraction: Ordering, zero: bool) -> bool {
+        match self {
+            RoundingMode::HalfUp => fraction != Ordering::Less,
+            RoundingMode::HalfEven | RoundingMode::HalfEvenExact => match fraction {
+                Ordering::Less => false,
+                Ordering::Equal => odd,
+                Ordering::Greater => true,
+            },
+            RoundingMode::Floor | RoundingMode::Truncate => false,
+            RoundingMode::Ceil => !zero,
+        }
+    }
+
+    /// Returns the mode that rounds the magnitude of a negative number the way this one
+    /// rounds the number itself.
+    pub(crate) fn mirrored(self) -> Self {
+        match self {
+            RoundingMode::Floor => RoundingMode::Ceil,
+            RoundingMode::Ceil => RoundingMode::Floor,
+            rounding => rounding,
+        }
+    }
+}
+
+/// A [`Size`] together with the [`SizeFormatter`] it is formatted with, returned by
+/// [`SizeFormatter::display()`].
+pub struct SizeDisplay<'a> {
+    formatter: &'a SizeFormatter<'a>,
+    size: Size,
+}
+
+impl fmt::Display for SizeDisplay<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let bytes = self.size.bytes();
+        let precision = fmt.precision();
+        pad(fmt, |out| {
+            self.formatter.format(out, bytes, precision, false)
+        })
+    }
+}
+
+impl fmt::Debug for SizeDisplay<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, fmt)
+    }
+}
+
+/// A [`SizeDelta`] together with the [`SizeFormatter`] it is formatted with, returned by
+/// [`SizeFormatter::display_delta()`].
+pub struct SizeDeltaDisplay<'a> {
+    formatter: &'a SizeFormatter<'a>,
+    /// The sign of the delta, which is empty for zero bytes
+    sign: &'static str,
+    /// The magnitude of the delta
+    bytes: u128,
+}
+
+impl fmt::Display for SizeDeltaDisplay<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let precision = fmt.precision();
+        let negative = self.sign == "-";
+        pad(fmt, |out| {
+            out.write_str(self.sign)?;
+            self.formatter.format(out, self.bytes, precision, negative)
+        })
+    }
+}
+
+impl fmt::Debug for SizeDeltaDisplay<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, fmt)
+    }
+}
+
+/// Writes the output of `write` to `fmt`, honoring its width, fill and alignment. The
+/// output is measured on a first pass so it can be padded without an intermediate
+/// `String`. Like strings, output is left-aligned unless requested otherwise.
+fn pad<F>(fmt: &mut fmt::Formatter, write: F) -> fmt::Result
+where
+    F: Fn(&mut dyn Write) -> fmt::Result,
+{
+    let width = match fmt.width() {
+        Some(width) => width,
+        None => return write(fmt),
+    };
+
+    let mut len = CharCount(0);
+    write(&mut len)?;
+    let padding = width.saturating_sub(len.0);
+    let (pre, post) = match fmt.align() {
+        Some(fmt::Alignment::Right) => (padding, 0),
+        Some(fmt::Alignment::Center) => (padding / 2, padding.div_ceil(2)),
+        Some(fmt::Alignment::Left) | None => (0, padding),
+    };
+
+    let fill = fmt.fill();
+    for _ in 0..pre {
+        fmt.write_char(fill)?;
+    }
+    write(fmt)?;
+    for _ in 0..post {
+        fmt.write_char(fill)?;
+    }
+    Ok(())
+}
+
+/// A `fmt::Write` sink that only counts the characters written to it.
+struct CharCount(usize);
+
+impl Write for CharCount {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.chars().count();
+        Ok(())
+    }
+}
+
+/// A single row of a [`FormatRules`] table: sizes below `less_than` bytes (and at or above
+/// the threshold of the previous row) are printed in `unit` with `decimals` decimal places.
+#[derive(Debug)]
+pub struct FormatRule {
+    less_than: u128,
+    unit: Unit,
+    decimals: usize,
+}
+
+impl FormatRule {
+    pub const fn new(less_than: u128, unit: Unit, decimals: usize) -> Self {
+        FormatRule {
+            less_than,
+            unit,
+            decimals,
+        }
+    }
+}
+
+/// A table of [`FormatRule`]s deciding which unit a size is printed in and with how many
+/// decimal places, sorted by ascending threshold. Sizes at or above the threshold of the
+/// last rule are formatted by that rule.
+///
+/// The tables used for [`Base::Base2`], [`Base::Base10`] and [`Base::Jedec`] are available
+/// as [`FormatRules::BASE2`], [`FormatRules::BASE10`] and [`FormatRules::JEDEC`]. Custom
+/// tables can be passed to [`SizeFormatter::with_rules()`], e.g. to print base-two units
+/// starting at 1000 bytes:
+///
+/// ```
+/// use size::{FormatRule, FormatRules, Size, SizeFormatter, Unit, KIBIBYTE};
+///
+/// const RULES: FormatRules = FormatRules::new(&[
+///     FormatRule::new(1000, Unit::Byte, 0),
+///     FormatRule::new(1000 * KIBIBYTE, Unit::Kibibyte, 1),
+///     FormatRule::new(u128::MAX, Unit::Mebibyte, 1),
+/// ]);
+///
+/// let formatter = SizeFormatter::new().with_rules(RULES);
+/// assert_eq!(formatter.display(Size::from_bytes(1000)).to_string(), "1.0 KiB");
+/// assert_eq!(formatter.display(Size::from_kib(999)).to_string(), "999.0 KiB");
+/// assert_eq!(formatter.display(Size::from_mib(1000)).to_string(), "1000.0 MiB");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FormatRules<'a> {
+    rules: &'a [FormatRule],
+    jedec_labels: bool,
+}
+
+impl<'a> FormatRules<'a> {
+    /// The rules used for [`Base::Base2`], switching to the next IEC unit every 1024×.
+    pub const BASE2: FormatRules<'static> = FormatRules::new(&BASE2_RULES);
+
+    /// The rules used for [`Base::Base10`], switching to the next SI unit every 1000×.
+    pub const BASE10: FormatRules<'static> = FormatRules::new(&BASE10_RULES);
+
+    /// The rules used for [`Base::Jedec`], which switch units like [`FormatRules::BASE2`]
+    /// but label them like JEDEC, e.g. "1.50 KB" for 1536 bytes.
+    pub const JEDEC: FormatRules<'static> = FormatRules::new(&BASE2_RULES).with_jedec_labels();
+
+    /// Creates a table from a list of rules.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rules` is empty or its thresholds are not strictly ascending.
+    pub const fn new(rules: &'a [FormatRule]) -> Self {
+        assert!(!rules.is_empty(), "a rule table needs at least one rule");
+        let mut i = 1;
+        while i < rules.len() {
+            assert!(
+                rules[i - 1].less_than < rules[i].less_than,
+                "rule thresholds must be strictly ascending"
+            );
+            i += 1;
+        }
+        FormatRules {
+            rules,
+            jedec_labels: false,
+        }
+    }
+
+    /// Labels the base-two units of the rules with the symbols and names of their base-ten
+    /// counterparts, e.g. "KB" and "Kilobytes" for kibibytes, as JEDEC does.
+    pub const fn with_jedec_labels(self) -> Self {
+        FormatRules {
+            jedec_labels: true,
+            ..self
+        }
+    }
+
+    /// Returns the unit whose symbol and name `unit` is printed with.
+    fn label(&self, unit: Unit) -> Unit {
+        match (self.jedec_labels, unit.base()) {
+            (true, Some(Base::Base2)) => unit.counterpart(),
+            _ => unit,
+        }
+    }
+
+    /// Returns the index of the rule whose range `bytes` falls in, before any rounding.
+    fn position(&self, bytes: u128) -> usize {
+        let index = self.rules.partition_point(|rule| rule.less_than <= bytes);
+        index.min(self.rules.len() - 1)
+    }
+}
+
+impl FormatRules<'static> {
+    fn for_base(base: &Base) -> Self {
+        match base {
+            Base::Base2 => FormatRules::BASE2,
+            Base::Base10 => FormatRules::BASE10,
+            Base::Jedec => FormatRules::JEDEC,
+        }
+    }
+}
+
+const BASE10_RULES: [FormatRule; 31] = [
+    FormatRule::new(KILOBYTE, Byte, 0),
+    FormatRule::new(10 * KILOBYTE, Kilobyte, 2),
+    FormatRule::new(100 * KILOBYTE, Kilobyte, 1),
+    FormatRule::new(MEGABYTE, Kilobyte, 0),
+    FormatRule::new(10 * MEGABYTE, Megabyte, 2),
+    FormatRule::new(100 * MEGABYTE, Megabyte, 1),
+    FormatRule::new(GIGABYTE, Megabyte, 0),
+    FormatRule::new(10 * GIGABYTE, Gigabyte, 2),
+    FormatRule::new(100 * GIGABYTE, Gigabyte, 1),
+    FormatRule::new(TERABYTE, Gigabyte, 0),
+    FormatRule::new(10 * TERABYTE, Terabyte, 2),
+    FormatRule::new(100 * TERABYTE, Terabyte, 1),
+    FormatRule::new(PETABYTE, Terabyte, 0),
+    FormatRule::new(10 * PETABYTE, Petabyte, 2),
+    FormatRule::new(100 * PETABYTE, Petabyte, 1),
+    FormatRule::new(EXABYTE, Petabyte, 0),
+    FormatRule::new(10 * EXABYTE, Exabyte, 2),
+    FormatRule::new(100 * EXABYTE, Exabyte, 1),
+    FormatRule::new(ZETTABYTE, Exabyte, 0),
+    FormatRule::new(10 * ZETTABYTE, Zettabyte, 2),
+    FormatRule::new(100 * ZETTABYTE, Zettabyte, 1),
+    FormatRule::new(YOTTABYTE, Zettabyte, 0),
+    FormatRule::new(10 * YOTTABYTE, Yottabyte, 2),
+    FormatRule::new(100 * YOTTABYTE, Yottabyte, 1),
+    FormatRule::new(RONNABYTE, Yottabyte, 0),
+    FormatRule::new(10 * RONNABYTE, Ronnabyte, 2),
+    FormatRule::new(100 * RONNABYTE, Ronnabyte, 1),
+    FormatRule::new(QUETTABYTE, Ronnabyte, 0),
+    FormatRule::new(10 * QUETTABYTE, Quettabyte, 2),
+    FormatRule::new(100 * QUETTABYTE, Quettabyte, 1),
+    FormatRule::new(u128::MAX, Quettabyte, 0),
+];
+
+const BASE2_RULES: [FormatRule; 31] = [
+    FormatRule::new(KIBIBYTE, Byte, 0),
+    FormatRule::new(10 * KIBIBYTE, Kibibyte, 2),
+    FormatRule::new(100 * KIBIBYTE, Kibibyte, 1),
+    FormatRule::new(MEBIBYTE, Kibibyte, 0),
+    FormatRule::new(10 * MEBIBYTE, Mebibyte, 2),
+    FormatRule::new(100 * MEBIBYTE, Mebibyte, 1),
+    FormatRule::new(GIBIBYTE, Mebibyte, 0),
+    FormatRule::new(10 * GIBIBYTE, Gibibyte, 2),
+    FormatRule::new(100 * GIBIBYTE, Gibibyte, 1),
+    FormatRule::new(TEBIBYTE, Gibibyte, 0),
+    FormatRule::new(10 * TEBIBYTE, Tebibyte, 2),
+    FormatRule::new(100 * TEBIBYTE, Tebibyte, 1),
+    FormatRule::new(PEBIBYTE, Tebibyte, 0),
+    FormatRule::new(10 * PEBIBYTE, Pebibyte, 2),
+    FormatRule::new(100 * PEBIBYTE, Pebibyte, 1),
+    FormatRule::new(EXBIBYTE, Pebibyte, 0),
+    FormatRule::new(10 * EXBIBYTE, Exbibyte, 2),
+    FormatRule::new(100 * EXBIBYTE, Exbibyte, 1),
+    FormatRule::new(ZEBIBYTE, Exbibyte, 0),
+    FormatRule::new(10 * ZEBIBYTE, Zebibyte, 2),
+    FormatRule::new(100 * ZEBIBYTE, Zebibyte, 1),
+    FormatRule::new(YOBIBYTE, Zebibyte, 0),
+    FormatRule::new(10 * YOBIBYTE, Yobibyte, 2),
+    FormatRule::new(100 * YOBIBYTE, Yobibyte, 1),
+    FormatRule::new(ROBIBYTE, Yobibyte, 0),
+    FormatRule::new(10 * ROBIBYTE, Robibyte, 2),
+    FormatRule::new(100 * ROBIBYTE, Robibyte, 1),
+    FormatRule::new(QUEBIBYTE, Robibyte, 0),
+    FormatRule::new(10 * QUEBIBYTE, Quebibyte, 2),
+    FormatRule::new(100 * QUEBIBYTE, Quebibyte, 1),
+    FormatRule::new(u128::MAX, Quebibyte, 0),
+];
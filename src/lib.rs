@@ -1,61 +1,115 @@
+mod delta;
+mod format;
+mod locale;
 mod ops;
 mod parse;
 #[cfg(test)]
 mod tests;
-
+mod typed;
+
+pub use self::delta::SizeDelta;
+pub use self::format::{FormatRule, FormatRules, Precision, RoundingMode};
+pub use self::format::{SizeDeltaDisplay, SizeDisplay, SizeFormatter};
+pub use self::locale::{Locale, PluralRule, UnitNames, UnitText};
+pub use self::parse::{Expected, FractionalBytes, ParseError, ParseErrorKind};
+pub use self::parse::{ParseMode, SizeParser};
+pub use self::typed::TypedSize;
 use self::Unit::*;
-use num_traits::FromPrimitive;
-use num_traits::ToPrimitive;
+use std::cmp::Ordering;
+use std::error;
+use std::ffi::OsStr;
 use std::fmt;
-use std::f64;
 use std::str::FromStr;
 
 const DEFAULT_BASE: Base = Base::Base2;
 const DEFAULT_STYLE: Style = Style::Smart;
 
-pub const BYTE: u64 = 1;
-pub const KILOBYTE: u64 = 1000;
-pub const MEGABYTE: u64 = 1000 * KILOBYTE;
-pub const GIGABYTE: u64 = 1000 * MEGABYTE;
-pub const TERABYTE: u64 = 1000 * GIGABYTE;
-pub const PETABYTE: u64 = 1000 * TERABYTE;
-pub const EXABYTE: u64 = 1000 * PETABYTE;
-
-pub const B: u64 = BYTE;
-pub const KB: u64 = KILOBYTE;
-pub const MB: u64 = MEGABYTE;
-pub const GB: u64 = GIGABYTE;
-pub const TB: u64 = TERABYTE;
-pub const PB: u64 = PETABYTE;
-pub const EB: u64 = EXABYTE;
-
-pub const KIBIBYTE: u64 = 1 << 10;
-pub const MEBIBYTE: u64 = 1 << 20;
-pub const GIBIBYTE: u64 = 1 << 30;
-pub const TEBIBYTE: u64 = 1 << 40;
-pub const PEBIBYTE: u64 = 1 << 50;
-pub const EXBIBYTE: u64 = 1 << 60;
+pub const BYTE: u128 = 1;
+pub const KILOBYTE: u128 = 1000;
+pub const MEGABYTE: u128 = 1000 * KILOBYTE;
+pub const GIGABYTE: u128 = 1000 * MEGABYTE;
+pub const TERABYTE: u128 = 1000 * GIGABYTE;
+pub const PETABYTE: u128 = 1000 * TERABYTE;
+pub const EXABYTE: u128 = 1000 * PETABYTE;
+pub const ZETTABYTE: u128 = 1000 * EXABYTE;
+pub const YOTTABYTE: u128 = 1000 * ZETTABYTE;
+pub const RONNABYTE: u128 = 1000 * YOTTABYTE;
+pub const QUETTABYTE: u128 = 1000 * RONNABYTE;
+
+pub const B: u128 = BYTE;
+pub const KB: u128 = KILOBYTE;
+pub const MB: u128 = MEGABYTE;
+pub const GB: u128 = GIGABYTE;
+pub const TB: u128 = TERABYTE;
+pub const PB: u128 = PETABYTE;
+pub const EB: u128 = EXABYTE;
+pub const ZB: u128 = ZETTABYTE;
+pub const YB: u128 = YOTTABYTE;
+pub const RB: u128 = RONNABYTE;
+pub const QB: u128 = QUETTABYTE;
+
+pub const KIBIBYTE: u128 = 1 << 10;
+pub const MEBIBYTE: u128 = 1 << 20;
+pub const GIBIBYTE: u128 = 1 << 30;
+pub const TEBIBYTE: u128 = 1 << 40;
+pub const PEBIBYTE: u128 = 1 << 50;
+pub const EXBIBYTE: u128 = 1 << 60;
+pub const ZEBIBYTE: u128 = 1 << 70;
+pub const YOBIBYTE: u128 = 1 << 80;
+/// 2^90 bytes. The robi- and quebi- prefixes were proposed alongside ronna- and quetta-,
+/// but aren't part of the IEC standard yet.
+pub const ROBIBYTE: u128 = 1 << 90;
+/// 2^100 bytes, see [`ROBIBYTE`].
+pub const QUEBIBYTE: u128 = 1 << 100;
 
 #[allow(non_upper_case_globals)]
-pub const KiB: u64 = KIBIBYTE;
+pub const KiB: u128 = KIBIBYTE;
+#[allow(non_upper_case_globals)]
+pub const MiB: u128 = MEBIBYTE;
+#[allow(non_upper_case_globals)]
+pub const GiB: u128 = GIBIBYTE;
+#[allow(non_upper_case_globals)]
+pub const TiB: u128 = TEBIBYTE;
 #[allow(non_upper_case_globals)]
-pub const MiB: u64 = MEBIBYTE;
+pub const PiB: u128 = PEBIBYTE;
 #[allow(non_upper_case_globals)]
-pub const GiB: u64 = GIBIBYTE;
+pub const EiB: u128 = EXBIBYTE;
 #[allow(non_upper_case_globals)]
-pub const TiB: u64 = TEBIBYTE;
+pub const ZiB: u128 = ZEBIBYTE;
 #[allow(non_upper_case_globals)]
-pub const PiB: u64 = PEBIBYTE;
+pub const YiB: u128 = YOBIBYTE;
 #[allow(non_upper_case_globals)]
-pub const EiB: u64 = EXBIBYTE;
+pub const RiB: u128 = ROBIBYTE;
+#[allow(non_upper_case_globals)]
+pub const QiB: u128 = QUEBIBYTE;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Base {
     Base2,
     Base10,
+    /// Base-two math with the symbols and names of the base-ten units, as defined by JEDEC
+    /// for memory and used by e.g. Windows Explorer, so that 1024 bytes are "1.00 KB".
+    Jedec,
+}
+
+impl fmt::Display for Base {
+    /// Writes `base-2`, `base-10` or `jedec`.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.pad(match self {
+            Base::Base2 => "base-2",
+            Base::Base10 => "base-10",
+            Base::Jedec => "jedec",
+        })
+    }
 }
 
-#[derive(Debug)]
+/// A unit of size. Units are ordered by their size, e.g. `Kilobyte < Kibibyte < Megabyte`.
+///
+/// `Robibyte` (RiB) and `Quebibyte` (QiB) are not standard IEC units: their prefixes were
+/// proposed alongside the SI ronna- and quetta-, but haven't been adopted yet. They are
+/// the largest base-two units, so formatting very large sizes in [`Base::Base2`] uses
+/// them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Unit {
     Byte,
     Kibibyte,
@@ -70,72 +124,168 @@ pub enum Unit {
     Petabyte,
     Exbibyte,
     Exabyte,
+    Zettabyte,
+    Zebibyte,
+    Yottabyte,
+    Yobibyte,
+    Ronnabyte,
+    /// 2^90 bytes, with a non-standard prefix.
+    Robibyte,
+    Quettabyte,
+    /// 2^100 bytes, with a non-standard prefix.
+    Quebibyte,
 }
 
 impl Unit {
-    fn text(&self) -> (&'static str, &'static str, &'static str, &'static str) {
-        match &self {
-            &Byte => ("byte", "Byte", "b", "B"),
-
-            &Kilobyte => ("kilobyte", "Kilobyte", "kb", "KB"),
-            &Megabyte => ("megabyte", "Megabyte", "mb", "MB"),
-            &Gigabyte => ("gigabyte", "Gigabyte", "gb", "GB"),
-            &Terabyte => ("terabyte", "Terabyte", "tb", "TB"),
-            &Petabyte => ("petabyte", "Petabyte", "pb", "PB"),
-            &Exabyte => ("exabyte", "Exabyte", "eb", "EB"),
-
-            &Kibibyte => ("kibibyte", "Kibibyte", "kib", "KiB"),
-            &Mebibyte => ("mebibyte", "Mebibyte", "mib", "MiB"),
-            &Gibibyte => ("gibibyte", "Gibibyte", "gib", "GiB"),
-            &Pebibyte => ("pebibyte", "Pebibyte", "pib", "PiB"),
-            &Tebibyte => ("tebibyte", "Tebibyte", "tib", "TiB"),
-            &Exbibyte => ("exbibyte", "Exbibyte", "eib", "EiB"),
+    /// Every unit, from the smallest to the largest.
+    pub const ALL: [Unit; 21] = [
+        Byte, Kilobyte, Kibibyte, Megabyte, Mebibyte, Gigabyte, Gibibyte, Terabyte, Tebibyte,
+        Petabyte, Pebibyte, Exabyte, Exbibyte, Zettabyte, Zebibyte, Yottabyte, Yobibyte, Ronnabyte,
+        Robibyte, Quettabyte, Quebibyte,
+    ];
+
+    /// Returns the number of bytes in one of the unit, e.g. 1024 for a kibibyte.
+    pub const fn bytes_per_unit(&self) -> u128 {
+        match self {
+            Byte => BYTE,
+
+            Kilobyte => KILOBYTE,
+            Megabyte => MEGABYTE,
+            Gigabyte => GIGABYTE,
+            Terabyte => TERABYTE,
+            Petabyte => PETABYTE,
+            Exabyte => EXABYTE,
+            Zettabyte => ZETTABYTE,
+            Yottabyte => YOTTABYTE,
+            Ronnabyte => RONNABYTE,
+            Quettabyte => QUETTABYTE,
+
+            Kibibyte => KIBIBYTE,
+            Mebibyte => MEBIBYTE,
+            Gibibyte => GIBIBYTE,
+            Tebibyte => TEBIBYTE,
+            Pebibyte => PEBIBYTE,
+            Exbibyte => EXBIBYTE,
+            Zebibyte => ZEBIBYTE,
+            Yobibyte => YOBIBYTE,
+            Robibyte => ROBIBYTE,
+            Quebibyte => QUEBIBYTE,
+        }
+    }
+
+    /// Returns the base of the unit's prefix, or `None` for bytes, which have no prefix.
+    pub const fn base(&self) -> Option<Base> {
+        match self {
+            Byte => None,
+            Kilobyte | Megabyte | Gigabyte | Terabyte | Petabyte | Exabyte | Zettabyte
+            | Yottabyte | Ronnabyte | Quettabyte => Some(Base::Base10),
+            Kibibyte | Mebibyte | Gibibyte | Tebibyte | Pebibyte | Exbibyte | Zebibyte
+            | Yobibyte | Robibyte | Quebibyte => Some(Base::Base2),
         }
     }
 
-    fn format(&self, mut fmt: &mut fmt::Formatter, bytes: u64, style: &Style) -> fmt::Result {
-        match style {
-            Style::Smart => match &self {
-                &Unit::Byte => self.format(&mut fmt, bytes, &Style::FullLowerCase),
-                _ => self.format(&mut fmt, bytes, &Style::Abbreviated),
-            },
-            style @ _ => match bytes {
-                1 => match style {
-                    Style::Smart => panic!("already covered above"),
-                    Style::FullLowerCase => write!(fmt, " {}", self.text().0),
-                    Style::Full => write!(fmt, " {}", self.text().1),
-                    Style::AbbreviatedLowerCase => write!(fmt, " {}", self.text().2),
-                    Style::Abbreviated => write!(fmt, " {}", self.text().3),
-                },
-                _ => match style {
-                    Style::Smart => panic!("already covered above"),
-                    Style::FullLowerCase => write!(fmt, " {}s", self.text().0),
-                    Style::Full => write!(fmt, " {}s", self.text().1),
-                    Style::AbbreviatedLowerCase => write!(fmt, " {}", self.text().2),
-                    Style::Abbreviated => write!(fmt, " {}", self.text().3),
-                },
-            },
+    /// Returns how many steps of the base (1000 or 1024) the unit is above a byte, e.g. 2
+    /// for both megabytes and mebibytes.
+    pub const fn exponent(&self) -> u32 {
+        match self {
+            Byte => 0,
+            Kilobyte | Kibibyte => 1,
+            Megabyte | Mebibyte => 2,
+            Gigabyte | Gibibyte => 3,
+            Terabyte | Tebibyte => 4,
+            Petabyte | Pebibyte => 5,
+            Exabyte | Exbibyte => 6,
+            Zettabyte | Zebibyte => 7,
+            Yottabyte | Yobibyte => 8,
+            Ronnabyte | Robibyte => 9,
+            Quettabyte | Quebibyte => 10,
         }
     }
+
+    /// Returns the unit with the same exponent in the other base, e.g. kilobytes for
+    /// kibibytes and vice versa. A byte is its own counterpart.
+    pub fn counterpart(&self) -> Unit {
+        match self {
+            Byte => Byte,
+            Kilobyte => Kibibyte,
+            Megabyte => Mebibyte,
+            Gigabyte => Gibibyte,
+            Terabyte => Tebibyte,
+            Petabyte => Pebibyte,
+            Exabyte => Exbibyte,
+            Zettabyte => Zebibyte,
+            Yottabyte => Yobibyte,
+            Ronnabyte => Robibyte,
+            Quettabyte => Quebibyte,
+            Kibibyte => Kilobyte,
+            Mebibyte => Megabyte,
+            Gibibyte => Gigabyte,
+            Tebibyte => Terabyte,
+            Pebibyte => Petabyte,
+            Exbibyte => Exabyte,
+            Zebibyte => Zettabyte,
+            Yobibyte => Yottabyte,
+            Robibyte => Ronnabyte,
+            Quebibyte => Quettabyte,
+        }
+    }
+
+    /// Returns the unit's symbol, e.g. `KiB`.
+    pub fn symbol(&self) -> &'static str {
+        UnitNames::EN.text(*self).symbol()
+    }
+
+    /// Returns the unit's full name in the singular and lowercase, e.g. `kibibyte`.
+    pub fn name(&self) -> &'static str {
+        UnitNames::EN.text(*self).singular()
+    }
+}
+
+impl fmt::Display for Unit {
+    /// Writes the unit's symbol, e.g. `KiB`.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.pad(self.symbol())
+    }
 }
 
-pub enum Size<T> {
-    Bytes(T),
-    Kibibytes(T),
-    Kilobytes(T),
-    Mebibytes(T),
-    Megabytes(T),
-    Gibibytes(T),
-    Gigabytes(T),
-    Tebibytes(T),
-    Terabytes(T),
-    Pebibytes(T),
-    Petabytes(T),
-    Exbibytes(T),
-    Exabytes(T),
+impl PartialOrd for Unit {
+    fn partial_cmp(&self, other: &Unit) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-#[derive(Debug)]
+impl Ord for Unit {
+    fn cmp(&self, other: &Unit) -> Ordering {
+        self.bytes_per_unit().cmp(&other.bytes_per_unit())
+    }
+}
+
+/// The reason a [`TypedSize`] doesn't have a number of bytes that fits in a `u128`, returned
+/// by [`TypedSize::try_bytes()`] and when converting it to a [`Size`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SizeError {
+    /// The size is negative.
+    Negative,
+    /// The size is infinite or NaN.
+    NotFinite,
+    /// The size is more than `u128::MAX` bytes.
+    Overflow,
+}
+
+impl fmt::Display for SizeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(match self {
+            SizeError::Negative => "size is negative",
+            SizeError::NotFinite => "size is not a finite number",
+            SizeError::Overflow => "size is too large to count its bytes in a u128",
+        })
+    }
+}
+
+impl error::Error for SizeError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Style {
     Abbreviated,
     AbbreviatedLowerCase,
@@ -144,347 +294,227 @@ pub enum Style {
     FullLowerCase,
 }
 
-impl<T> std::fmt::Display for Size<T>
-where
-    T: ToPrimitive,
-{
+impl fmt::Display for Style {
+    /// Writes the name of the style in kebab case, e.g. `abbreviated-lowercase`.
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        self.format(fmt, &DEFAULT_BASE, &DEFAULT_STYLE)
+        fmt.pad(match self {
+            Style::Abbreviated => "abbreviated",
+            Style::AbbreviatedLowerCase => "abbreviated-lowercase",
+            Style::Full => "full",
+            Style::Smart => "smart",
+            Style::FullLowerCase => "full-lowercase",
+        })
     }
 }
 
-impl<T> std::fmt::Debug for Size<T>
-where
-    T: ToPrimitive,
-{
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{} bytes", self.bytes())
-    }
+/// A size, counted in whole bytes. `Size` is as cheap to copy and compare as the `u128` it
+/// holds, and can be created in `const` items:
+///
+/// ```
+/// use size::Size;
+///
+/// const BLOCK: Size = Size::from_kib(4);
+/// assert_eq!(BLOCK.bytes(), 4096);
+/// assert!(Size::from_kb(4) < BLOCK);
+/// assert_eq!(format!("{}", BLOCK * 512), "2.00 MiB");
+/// ```
+///
+/// Sizes that aren't a whole number of some unit, such as 1.5 GiB, are written as a
+/// [`TypedSize`] and converted with `Size::try_from(TypedSize::Gibibytes(1.5))`.
+///
+/// `Size` used to be the enum that is now [`TypedSize`]. Its variants are still around as
+/// deprecated constructors, so that `Size::Kibibytes(4)` creates a `Size` of 4 KiB, but
+/// matching on them needs `TypedSize` instead.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Size {
+    bytes: u128,
 }
 
-impl<T, U> PartialEq<Size<U>> for Size<T>
-where
-    T: ToPrimitive,
-    U: ToPrimitive,
-{
-    fn eq(&self, other: &Size<U>) -> bool {
-        self.bytes() == other.bytes()
-    }
+/// Defines a `const` constructor for a number of some unit, e.g. `Size::from_kib()`.
+macro_rules! unit_constructor {
+    ($name:ident, $unit:ident, $plural:literal) => {
+        #[doc = concat!("Creates a size of `value` ", $plural, ".")]
+        ///
+        /// # Panics
+        ///
+        /// Panics if the size is more than `u128::MAX` bytes, like [`Size::from_unit()`].
+        pub const fn $name(value: u128) -> Size {
+            Size::from_unit(value, Unit::$unit)
+        }
+    };
 }
 
-struct Fmt<F>(pub F)
-where
-    F: Fn(&mut fmt::Formatter) -> fmt::Result;
+impl Size {
+    /// A size of zero bytes.
+    pub const ZERO: Size = Size { bytes: 0 };
+
+    /// The largest size, `u128::MAX` bytes.
+    pub const MAX: Size = Size { bytes: u128::MAX };
 
-impl<F> fmt::Debug for Fmt<F>
-where
-    F: Fn(&mut fmt::Formatter) -> fmt::Result,
-{
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        (self.0)(f)
+    /// Creates a size of `bytes` bytes.
+    pub const fn from_bytes(bytes: u128) -> Size {
+        Size { bytes }
     }
-}
 
-impl<T> Size<T>
-where
-    T: ToPrimitive,
-{
-    pub fn bytes(&self) -> u64 {
-        use self::Size::*;
-
-        let (i, f, unit) = match &self {
-            &Bytes(x)     => (x.to_u64(), x.to_f64(), 1),
-            &Kilobytes(x) => (x.to_u64(), x.to_f64(), KILOBYTE),
-            &Megabytes(x) => (x.to_u64(), x.to_f64(), MEGABYTE),
-            &Gigabytes(x) => (x.to_u64(), x.to_f64(), GIGABYTE),
-            &Terabytes(x) => (x.to_u64(), x.to_f64(), TERABYTE),
-            &Petabytes(x) => (x.to_u64(), x.to_f64(), PETABYTE),
-            &Exabytes(x)  => (x.to_u64(), x.to_f64(), EXABYTE ),
-            &Kibibytes(x) => (x.to_u64(), x.to_f64(), KIBIBYTE),
-            &Mebibytes(x) => (x.to_u64(), x.to_f64(), MEBIBYTE),
-            &Gibibytes(x) => (x.to_u64(), x.to_f64(), GIBIBYTE),
-            &Tebibytes(x) => (x.to_u64(), x.to_f64(), TEBIBYTE),
-            &Pebibytes(x) => (x.to_u64(), x.to_f64(), PEBIBYTE),
-            &Exbibytes(x) => (x.to_u64(), x.to_f64(), EXBIBYTE),
-        };
-        let (i, f) = match (i, f) {
-            (_, Some(f)) if f.fract() > f64::EPSILON => (None, Some(f)),
-            t @ (None, Some(_)) => t,
-            (i, _) => (i, None),
-        };
-        match (i, f) {
-            (_, Some(f)) =>
-                // This will cause Undefined Behavior if the f64 cannot be represented by a u64
-                (f * unit as f64) as u64,
-            (Some(i), _) =>
-                // This will panic with overflow
-                i * unit,
-            _ => 0,
+    /// Creates a size of `value` in the given unit.
+    ///
+    /// ```
+    /// use size::{Size, Unit};
+    ///
+    /// assert_eq!(Size::from_unit(2, Unit::Kibibyte), Size::from_bytes(2048));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size is more than `u128::MAX` bytes, which fails to compile in a
+    /// `const` item. Convert a [`TypedSize`] with `Size::try_from()` to handle that instead.
+    pub const fn from_unit(value: u128, unit: Unit) -> Size {
+        match value.checked_mul(unit.bytes_per_unit()) {
+            Some(bytes) => Size { bytes },
+            None => panic!("size is too large to count its bytes in a u128"),
         }
     }
 
+    unit_constructor!(from_kb, Kilobyte, "kilobytes");
+    unit_constructor!(from_kib, Kibibyte, "kibibytes");
+    unit_constructor!(from_mb, Megabyte, "megabytes");
+    unit_constructor!(from_mib, Mebibyte, "mebibytes");
+    unit_constructor!(from_gb, Gigabyte, "gigabytes");
+    unit_constructor!(from_gib, Gibibyte, "gibibytes");
+    unit_constructor!(from_tb, Terabyte, "terabytes");
+    unit_constructor!(from_tib, Tebibyte, "tebibytes");
+    unit_constructor!(from_pb, Petabyte, "petabytes");
+    unit_constructor!(from_pib, Pebibyte, "pebibytes");
+    unit_constructor!(from_eb, Exabyte, "exabytes");
+    unit_constructor!(from_eib, Exbibyte, "exbibytes");
+    unit_constructor!(from_zb, Zettabyte, "zettabytes");
+    unit_constructor!(from_zib, Zebibyte, "zebibytes");
+    unit_constructor!(from_yb, Yottabyte, "yottabytes");
+    unit_constructor!(from_yib, Yobibyte, "yobibytes");
+    unit_constructor!(from_rb, Ronnabyte, "ronnabytes");
+    unit_constructor!(from_rib, Robibyte, "robibytes");
+    unit_constructor!(from_qb, Quettabyte, "quettabytes");
+    unit_constructor!(from_qib, Quebibyte, "quebibytes");
+
+    /// Returns the number of bytes in the size.
+    pub const fn bytes(&self) -> u128 {
+        self.bytes
+    }
+
+    /// Returns the size as a number of `unit`, e.g. `1.5` for 1536 bytes in kibibytes.
+    pub fn in_unit(&self, unit: Unit) -> f64 {
+        // Dividing the whole and fractional parts separately keeps more precision than
+        // converting byte counts past 2^53 to `f64` first
+        let (bytes, divisor) = (self.bytes, unit.bytes_per_unit());
+        (bytes / divisor) as f64 + (bytes % divisor) as f64 / divisor as f64
+    }
+
+    /// Returns the number of whole `unit`s in the size, along with the rest.
+    ///
+    /// ```
+    /// use size::{Size, Unit};
+    ///
+    /// let size = Size::from_bytes(1536);
+    /// assert_eq!(size.in_unit(Unit::Kibibyte), 1.5);
+    /// assert_eq!(size.in_whole_units(Unit::Kibibyte), (1, Size::from_bytes(512)));
+    /// ```
+    pub fn in_whole_units(&self, unit: Unit) -> (u128, Size) {
+        let (bytes, divisor) = (self.bytes, unit.bytes_per_unit());
+        (bytes / divisor, Size::from_bytes(bytes % divisor))
+    }
+
+    /// Returns the size in the unit it is displayed in for `base`, picked by the same rules
+    /// as formatting it with a [`SizeFormatter`] for that base.
+    ///
+    /// ```
+    /// use size::{Base, Size, TypedSize};
+    ///
+    /// let size = Size::from_kib(2048);
+    /// let mebibytes = size.normalize(Base::Base2);
+    /// assert!(matches!(mebibytes, TypedSize::Mebibytes(value) if value == 2.0));
+    /// let megabytes = size.normalize(Base::Base10);
+    /// assert!(matches!(megabytes, TypedSize::Megabytes(value) if value == 2.097152));
+    /// ```
+    pub fn normalize(&self, base: Base) -> TypedSize<f64> {
+        let unit = SizeFormatter::new().with_base(base).unit(self.bytes);
+        TypedSize::from_unit(self.in_unit(unit), unit)
+    }
+
+    /// Formats the size in the given base and style. This is a shorthand for building a
+    /// [`SizeFormatter`] and displaying the size with it.
+    pub fn to_string_with(&self, base: Base, style: Style) -> String {
+        SizeFormatter::new()
+            .with_base(base)
+            .with_style(style)
+            .display(*self)
+            .to_string()
+    }
+
+    /// Formats the size in the given base and style, like [`Size::to_string_with()`].
+    #[deprecated(note = "shadows `ToString::to_string`; use `Size::to_string_with` or \
+                         `SizeFormatter::display` instead")]
     pub fn to_string(&self, base: Base, style: Style) -> String {
-        return format!("{:?}", Fmt(|f| self.format(f, &base, &style)));
+        self.to_string_with(base, style)
     }
 
-    fn format(&self, mut fmt: &mut fmt::Formatter, base: &Base, style: &Style) -> fmt::Result {
-        let bytes = self.bytes();
-
-        let rule = match base {
-            Base::Base2 => match BASE2_RULES.binary_search_by_key(&bytes, |rule| rule.less_than) {
-                Ok(index) => &BASE2_RULES[index + 1],
-                Err(index) => &BASE2_RULES[index],
-            },
-            Base::Base10 => {
-                match BASE10_RULES.binary_search_by_key(&bytes, |rule| rule.less_than) {
-                    Ok(index) => &BASE10_RULES[index + 1],
-                    Err(index) => &BASE10_RULES[index],
-                }
-            }
-        };
+    /// Parses a size from bytes that need not be valid UTF-8, with the same grammar as
+    /// [`FromStr`]. The default grammar only accepts ASCII, so any other byte is rejected.
+    /// This is a shorthand for [`SizeParser::parse_bytes()`] with the default options, and
+    /// a configured parser may also accept non-ASCII text.
+    ///
+    /// ```
+    /// use size::Size;
+    ///
+    /// let size = Size::parse_bytes(b"2.50 MiB").unwrap();
+    /// assert_eq!(size, Size::from_kib(2560));
+    /// assert!(Size::parse_bytes(b"2.50 \xffiB").is_err());
+    /// ```
+    pub fn parse_bytes(input: &[u8]) -> Result<Size, ParseError> {
+        SizeParser::new().parse_bytes(input)
+    }
 
-        (rule.formatter)(&mut fmt, bytes)?;
-        rule.unit.format(&mut fmt, bytes, &style)?;
+    /// Parses a size from an `OsStr`, such as a command line argument, with the same
+    /// grammar as [`FromStr`] and without requiring it to be valid Unicode. This is a
+    /// shorthand for [`SizeParser::parse_os_str()`] with the default options.
+    pub fn parse_os_str(input: &OsStr) -> Result<Size, ParseError> {
+        SizeParser::new().parse_os_str(input)
+    }
+}
 
-        return Ok(());
+impl fmt::Display for Size {
+    /// Formats the size in the default base and style, honoring the standard format
+    /// specifiers: width, fill and alignment pad the whole output (left-aligned by
+    /// default, as for strings), precision overrides the number of decimal places chosen
+    /// by the formatting rules, and the alternate flag (`{:#}`) switches to
+    /// [`Base::Base10`] units.
+    ///
+    /// ```
+    /// use size::Size;
+    ///
+    /// let size = Size::from_kib(2048);
+    /// assert_eq!(format!("{}", size), "2.00 MiB");
+    /// assert_eq!(format!("{:>10.1}", size), "   2.0 MiB");
+    /// assert_eq!(format!("{:#}", size), "2.10 MB");
+    /// ```
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let base = match fmt.alternate() {
+            true => Base::Base10,
+            false => DEFAULT_BASE,
+        };
+        SizeFormatter::new().with_base(base).display(*self).fmt(fmt)
     }
 }
 
-struct FormatRule {
-    less_than: u64,
-    formatter: fn(&mut fmt::Formatter, bytes: u64) -> fmt::Result,
-    unit: Unit,
+impl fmt::Debug for Size {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{} bytes", self.bytes)
+    }
 }
 
-const BASE10_RULES: [FormatRule; 19] = [
-    FormatRule {
-        less_than: 0,
-        formatter: |_, _| panic!("u64 less than zero!"),
-        unit: Byte,
-    },
-    FormatRule {
-        less_than: 1 * KILOBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes),
-        unit: Byte,
-    },
-    FormatRule {
-        less_than: 10 * KILOBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * KILOBYTE) as f64)),
-        unit: Kilobyte,
-    },
-    FormatRule {
-        less_than: 100 * KILOBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * KILOBYTE) as f64)),
-        unit: Kilobyte,
-    },
-    FormatRule {
-        less_than: 1 * MEGABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * KILOBYTE) as f64)),
-        unit: Kilobyte,
-    },
-    FormatRule {
-        less_than: 10 * MEGABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * MEGABYTE) as f64)),
-        unit: Megabyte,
-    },
-    FormatRule {
-        less_than: 100 * MEGABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * MEGABYTE) as f64)),
-        unit: Megabyte,
-    },
-    FormatRule {
-        less_than: 1 * GIGABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * MEGABYTE) as f64)),
-        unit: Megabyte,
-    },
-    FormatRule {
-        less_than: 10 * GIGABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * GIGABYTE) as f64)),
-        unit: Gigabyte,
-    },
-    FormatRule {
-        less_than: 100 * GIGABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * GIGABYTE) as f64)),
-        unit: Gigabyte,
-    },
-    FormatRule {
-        less_than: 1 * TERABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * GIGABYTE) as f64)),
-        unit: Gigabyte,
-    },
-    FormatRule {
-        less_than: 10 * TERABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * TERABYTE) as f64)),
-        unit: Terabyte,
-    },
-    FormatRule {
-        less_than: 100 * TERABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * TERABYTE) as f64)),
-        unit: Terabyte,
-    },
-    FormatRule {
-        less_than: 1 * PETABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * TERABYTE) as f64)),
-        unit: Terabyte,
-    },
-    FormatRule {
-        less_than: 10 * PETABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * PETABYTE) as f64)),
-        unit: Petabyte,
-    },
-    FormatRule {
-        less_than: 100 * PETABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * PETABYTE) as f64)),
-        unit: Petabyte,
-    },
-    FormatRule {
-        less_than: 1 * EXABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * PETABYTE) as f64)),
-        unit: Petabyte,
-    },
-    FormatRule {
-        less_than: 10 * EXABYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * EXABYTE) as f64)),
-        unit: Exabyte,
-    },
-    FormatRule {
-        less_than: u64::max_value(),
-        formatter: |fmt, bytes| write!(fmt, "{:0}", bytes as f64 / ((1u64 * EXABYTE) as f64)),
-        unit: Exabyte,
-    },
-];
-
-const BASE2_RULES: [FormatRule; 19] = [
-    FormatRule {
-        less_than: 0,
-        formatter: |_, _| panic!("u64 less than zero!"),
-        unit: Byte,
-    },
-    FormatRule {
-        less_than: 1 * KIBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes),
-        unit: Byte,
-    },
-    FormatRule {
-        less_than: 10 * KIBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * KIBIBYTE) as f64)),
-        unit: Kibibyte,
-    },
-    FormatRule {
-        less_than: 100 * KIBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * KIBIBYTE) as f64)),
-        unit: Kibibyte,
-    },
-    FormatRule {
-        less_than: 1 * MEBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * KIBIBYTE) as f64)),
-        unit: Kibibyte,
-    },
-    FormatRule {
-        less_than: 10 * MEBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * MEBIBYTE) as f64)),
-        unit: Mebibyte,
-    },
-    FormatRule {
-        less_than: 100 * MEBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * MEBIBYTE) as f64)),
-        unit: Mebibyte,
-    },
-    FormatRule {
-        less_than: 1 * GIBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * MEBIBYTE) as f64)),
-        unit: Mebibyte,
-    },
-    FormatRule {
-        less_than: 10 * GIBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * GIBIBYTE) as f64)),
-        unit: Gibibyte,
-    },
-    FormatRule {
-        less_than: 100 * GIBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * GIBIBYTE) as f64)),
-        unit: Gibibyte,
-    },
-    FormatRule {
-        less_than: 1 * TEBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * GIBIBYTE) as f64)),
-        unit: Gibibyte,
-    },
-    FormatRule {
-        less_than: 10 * TEBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * TEBIBYTE) as f64)),
-        unit: Tebibyte,
-    },
-    FormatRule {
-        less_than: 100 * TEBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * TEBIBYTE) as f64)),
-        unit: Tebibyte,
-    },
-    FormatRule {
-        less_than: 1 * PEBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * TEBIBYTE) as f64)),
-        unit: Tebibyte,
-    },
-    FormatRule {
-        less_than: 10 * PEBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * PEBIBYTE) as f64)),
-        unit: Pebibyte,
-    },
-    FormatRule {
-        less_than: 100 * PEBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.1}", bytes as f64 / ((1u64 * PEBIBYTE) as f64)),
-        unit: Pebibyte,
-    },
-    FormatRule {
-        less_than: 1 * EXBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.0}", bytes as f64 / ((1u64 * PEBIBYTE) as f64)),
-        unit: Pebibyte,
-    },
-    FormatRule {
-        less_than: 10 * EXBIBYTE,
-        formatter: |fmt, bytes| write!(fmt, "{:.2}", bytes as f64 / ((1u64 * EXBIBYTE) as f64)),
-        unit: Exbibyte,
-    },
-    FormatRule {
-        less_than: u64::max_value(),
-        formatter: |fmt, bytes| write!(fmt, "{:0}", bytes as f64 / ((1u64 * EXBIBYTE) as f64)),
-        unit: Exbibyte,
-    },
-];
-
-impl<T> FromStr for Size<T>
-where
-    T: FromPrimitive,
-{
-    type Err = parse::ScanError;
+impl FromStr for Size {
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use self::Size::*;
-
-        let mut lex = parse::Lexer::new(s);
-        let num = lex.read_number()?;
-        let unit = match lex.read_unit() {
-            Err(parse::ScanError::TokenError(parse::Error::EOF)) => Ok(Unit::Byte),
-            unit @ _ => unit,
-        }?;
-        lex.read_eof()?;
-
-        let num: T = FromPrimitive::from_f64(num).unwrap();
-        let size = match unit {
-            Unit::Byte => Bytes::<T>(num),
-            Unit::Kibibyte => Kibibytes::<T>(num),
-            Unit::Kilobyte => Kilobytes::<T>(num),
-            Unit::Mebibyte => Mebibytes::<T>(num),
-            Unit::Megabyte => Megabytes::<T>(num),
-            Unit::Gibibyte => Gibibytes::<T>(num),
-            Unit::Gigabyte => Gigabytes::<T>(num),
-            Unit::Tebibyte => Tebibytes::<T>(num),
-            Unit::Terabyte => Terabytes::<T>(num),
-            Unit::Pebibyte => Pebibytes::<T>(num),
-            Unit::Petabyte => Petabytes::<T>(num),
-            Unit::Exbibyte => Exbibytes::<T>(num),
-            Unit::Exabyte => Exabytes::<T>(num),
-        };
-        Ok(size)
+        SizeParser::new().parse(s)
     }
 }
@@ -0,0 +1,573 @@
+//! Conventions for writing sizes in different languages: how numbers are written and what
+//! the units are called. Both are shared by [`SizeFormatter`](crate::SizeFormatter) and
+//! [`SizeParser`](crate::SizeParser), so that sizes are read back the way they are printed.
+
+use crate::{Base, Style, Unit};
+use std::fmt::{self, Write};
+use std::str;
+use std::sync::{PoisonError, RwLock};
+
+/// How the number in a size is written: the decimal separator, how the digits before it
+/// are grouped, and the text between the number and the unit.
+///
+/// The default, [`Locale::new()`], is what `Display` and `FromStr` use: a decimal point, no
+/// grouping and a single space. A few common conventions are built in, and others can be
+/// made by adjusting one of them.
+///
+/// ```
+/// use size::{Locale, Size, SizeFormatter, SizeParser};
+///
+/// let formatter = SizeFormatter::new().with_locale(Locale::DE);
+/// assert_eq!(formatter.display(Size::from_kib(2560)).to_string(), "2,50 MiB");
+/// assert_eq!(formatter.display(Size::from_bytes(1000)).to_string(), "1.000 bytes");
+///
+/// let parser = SizeParser::new().with_locale(Locale::DE);
+/// assert_eq!(parser.parse("1.024,5 KiB").unwrap(), Size::from_bytes(1_049_088));
+///
+/// let swiss = Locale::DE.with_grouping('\'', 3);
+/// let formatter = SizeFormatter::new().with_locale(swiss);
+/// assert_eq!(formatter.display(Size::from_bytes(1000)).to_string(), "1'000 bytes");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    decimal_separator: char,
+    grouping: Option<(char, usize)>,
+    unit_separator: &'static str,
+}
+
+impl Locale {
+    /// English conventions: "1,023.5 KiB".
+    pub const EN: Locale = Locale::new().with_grouping(',', 3);
+
+    /// German conventions: "1.023,5 KiB".
+    pub const DE: Locale = Locale::new()
+        .with_decimal_separator(',')
+        .with_grouping('.', 3);
+
+    /// French conventions, which group digits with a narrow no-break space and keep the
+    /// number and unit together with a no-break space: "1 023,5 KiB".
+    pub const FR: Locale = Locale::new()
+        .with_decimal_separator(',')
+        .with_grouping('\u{202f}', 3)
+        .with_unit_separator("\u{a0}");
+
+    /// Creates the locale used by default: "1023.5 KiB".
+    pub const fn new() -> Self {
+        Locale {
+            decimal_separator: '.',
+            grouping: None,
+            unit_separator: " ",
+        }
+    }
+
+    /// Sets the character between the whole part of a number and its decimal places.
+    pub const fn with_decimal_separator(self, decimal_separator: char) -> Self {
+        Locale {
+            decimal_separator,
+            ..self
+        }
+    }
+
+    /// Groups the digits before the decimal separator by `size` from the right, splitting
+    /// the groups with `separator`. A size of zero turns grouping off.
+    pub const fn with_grouping(self, separator: char, size: usize) -> Self {
+        let grouping = match size {
+            0 => None,
+            size => Some((separator, size)),
+        };
+        Locale { grouping, ..self }
+    }
+
+    /// Sets the text between the number and the unit.
+    pub const fn with_unit_separator(self, unit_separator: &'static str) -> Self {
+        Locale {
+            unit_separator,
+            ..self
+        }
+    }
+
+    /// Returns the character between the whole part of a number and its decimal places.
+    pub const fn decimal_separator(&self) -> char {
+        self.decimal_separator
+    }
+
+    /// Returns the character splitting groups of digits and the size of the groups, if
+    /// digits are grouped.
+    pub const fn grouping(&self) -> Option<(char, usize)> {
+        self.grouping
+    }
+
+    /// Returns the text between the number and the unit.
+    pub const fn unit_separator(&self) -> &'static str {
+        self.unit_separator
+    }
+
+    /// Returns whether numbers are written the same as with no locale at all.
+    pub(crate) fn is_plain(&self) -> bool {
+        self.decimal_separator == '.' && self.grouping.is_none()
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::new()
+    }
+}
+
+/// How a language picks the form of a unit name that goes with a number, from the number
+/// as it is printed: "1 byte" but "1.00 kibibytes".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluralRule {
+    /// The singular goes with exactly 1 and the plural with any other number, including
+    /// one with decimals, like in English and German.
+    English,
+    /// The singular goes with any number below 2, including 0 and one with decimals, like
+    /// in French.
+    French,
+    /// The singular goes with whole numbers ending in 1 ("21 байт"), the paucal with whole
+    /// numbers ending in 2 to 4 ("3 байта") and with any number with decimals, and the
+    /// plural with the other whole numbers, which end in 5 to 9, 0 or 11 to 14, like in
+    /// Russian.
+    Russian,
+}
+
+impl PluralRule {
+    /// Picks the form of `text` for a number whose whole part is `whole`, and which has
+    /// decimal places (or an exponent) if `fraction` is set.
+    fn pick(self, text: &UnitText, whole: u128, fraction: bool) -> &'static str {
+        match self {
+            PluralRule::English if whole == 1 && !fraction => text.singular,
+            PluralRule::French if whole < 2 => text.singular,
+            PluralRule::Russian if fraction => text.paucal,
+            PluralRule::Russian => match (whole % 10, whole % 100) {
+                (_, 11..=14) => text.plural,
+                (1, _) => text.singular,
+                (2..=4, _) => text.paucal,
+                _ => text.plural,
+            },
+            _ => text.plural,
+        }
+    }
+}
+
+/// The words for one unit in a [`UnitNames`] vocabulary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnitText {
+    singular: &'static str,
+    plural: &'static str,
+    paucal: &'static str,
+    symbol: &'static str,
+}
+
+impl UnitText {
+    /// Creates the words for a unit from its full name in the singular and plural, as they
+    /// are written in the middle of a sentence, and its symbol.
+    pub const fn new(singular: &'static str, plural: &'static str, symbol: &'static str) -> Self {
+        UnitText {
+            singular,
+            plural,
+            paucal: plural,
+            symbol,
+        }
+    }
+
+    /// Sets the full name used for a few of the unit with [`PluralRule::Russian`], e.g.
+    /// `килобайта`. It is the plural until set.
+    pub const fn with_paucal(self, paucal: &'static str) -> Self {
+        UnitText { paucal, ..self }
+    }
+
+    /// Returns the full name used for a single byte, e.g. `kibibyte`.
+    pub const fn singular(&self) -> &'static str {
+        self.singular
+    }
+
+    /// Returns the full name used for any other amount, e.g. `kibibytes`.
+    pub const fn plural(&self) -> &'static str {
+        self.plural
+    }
+
+    /// Returns the full name used for a few of the unit, e.g. `килобайта`.
+    pub const fn paucal(&self) -> &'static str {
+        self.paucal
+    }
+
+    /// Returns the symbol, e.g. `KiB`.
+    pub const fn symbol(&self) -> &'static str {
+        self.symbol
+    }
+}
+
+/// The names and symbols of every unit in one language, which a [`SizeFormatter`] prints in
+/// place of the English ones with [`SizeFormatter::with_unit_names()`] and a [`SizeParser`]
+/// accepts with [`SizeParser::with_unit_names()`]. The [`Style`] still decides between
+/// names and symbols and their case.
+///
+/// A vocabulary has a singular and a plural form of each name, and a [`PluralRule`] that
+/// picks one of them for the number as it is printed. Languages that also decline names
+/// for a few of a unit, like Russian, have a third form, the paucal.
+///
+/// The built-in vocabularies, and any others passed to [`UnitNames::register()`], can be
+/// looked up by their language with [`UnitNames::for_language()`]:
+///
+/// ```
+/// use size::{Size, SizeFormatter, Style, UnitNames, UnitText};
+///
+/// let formatter = SizeFormatter::new().with_unit_names(UnitNames::FR);
+/// assert_eq!(formatter.display(Size::from_kib(2)).to_string(), "2.00 Kio");
+/// let formatter = formatter.with_style(Style::Full);
+/// assert_eq!(formatter.display(Size::from_kib(2)).to_string(), "2.00 Kibioctets");
+///
+/// static PIRATE: UnitNames = UnitNames::EN.with_language("en-pirate").with_unit(
+///     size::Unit::Byte,
+///     UnitText::new("doubloon", "doubloons", "D"),
+/// );
+/// UnitNames::register(&PIRATE);
+/// let names = UnitNames::for_language("en-pirate").unwrap();
+/// let formatter = formatter.with_unit_names(names);
+/// assert_eq!(formatter.display(Size::from_bytes(12)).to_string(), "12 Doubloons");
+/// ```
+///
+/// [`SizeFormatter`]: crate::SizeFormatter
+/// [`SizeFormatter::with_unit_names()`]: crate::SizeFormatter::with_unit_names
+/// [`SizeParser`]: crate::SizeParser
+/// [`SizeParser::with_unit_names()`]: crate::SizeParser::with_unit_names
+/// [`Style`]: crate::Style
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnitNames {
+    language: &'static str,
+    units: [UnitText; 21],
+    plural_rule: PluralRule,
+}
+
+/// Vocabularies added with [`UnitNames::register()`], most recent last.
+static REGISTERED: RwLock<Vec<&'static UnitNames>> = RwLock::new(Vec::new());
+
+impl UnitNames {
+    /// English, the vocabulary used by default.
+    pub const EN: &'static UnitNames = &UnitNames::new(
+        "en",
+        [
+            UnitText::new("byte", "bytes", "B"),
+            UnitText::new("kilobyte", "kilobytes", "KB"),
+            UnitText::new("kibibyte", "kibibytes", "KiB"),
+            UnitText::new("megabyte", "megabytes", "MB"),
+            UnitText::new("mebibyte", "mebibytes", "MiB"),
+            UnitText::new("gigabyte", "gigabytes", "GB"),
+            UnitText::new("gibibyte", "gibibytes", "GiB"),
+            UnitText::new("terabyte", "terabytes", "TB"),
+            UnitText::new("tebibyte", "tebibytes", "TiB"),
+            UnitText::new("petabyte", "petabytes", "PB"),
+            UnitText::new("pebibyte", "pebibytes", "PiB"),
+            UnitText::new("exabyte", "exabytes", "EB"),
+            UnitText::new("exbibyte", "exbibytes", "EiB"),
+            UnitText::new("zettabyte", "zettabytes", "ZB"),
+            UnitText::new("zebibyte", "zebibytes", "ZiB"),
+            UnitText::new("yottabyte", "yottabytes", "YB"),
+            UnitText::new("yobibyte", "yobibytes", "YiB"),
+            UnitText::new("ronnabyte", "ronnabytes", "RB"),
+            UnitText::new("robibyte", "robibytes", "RiB"),
+            UnitText::new("quettabyte", "quettabytes", "QB"),
+            UnitText::new("quebibyte", "quebibytes", "QiB"),
+        ],
+    );
+
+    /// French, which counts octets: "2,00 Kio".
+    pub const FR: &'static UnitNames = &UnitNames::new(
+        "fr",
+        [
+            UnitText::new("octet", "octets", "o"),
+            UnitText::new("kilooctet", "kilooctets", "ko"),
+            UnitText::new("kibioctet", "kibioctets", "Kio"),
+            UnitText::new("mégaoctet", "mégaoctets", "Mo"),
+            UnitText::new("mébioctet", "mébioctets", "Mio"),
+            UnitText::new("gigaoctet", "gigaoctets", "Go"),
+            UnitText::new("gibioctet", "gibioctets", "Gio"),
+            UnitText::new("téraoctet", "téraoctets", "To"),
+            UnitText::new("tébioctet", "tébioctets", "Tio"),
+            UnitText::new("pétaoctet", "pétaoctets", "Po"),
+            UnitText::new("pébioctet", "pébioctets", "Pio"),
+            UnitText::new("exaoctet", "exaoctets", "Eo"),
+            UnitText::new("exbioctet", "exbioctets", "Eio"),
+            UnitText::new("zettaoctet", "zettaoctets", "Zo"),
+            UnitText::new("zébioctet", "zébioctets", "Zio"),
+            UnitText::new("yottaoctet", "yottaoctets", "Yo"),
+            UnitText::new("yobioctet", "yobioctets", "Yio"),
+            UnitText::new("ronnaoctet", "ronnaoctets", "Ro"),
+            UnitText::new("robioctet", "robioctets", "Rio"),
+            UnitText::new("quettaoctet", "quettaoctets", "Qo"),
+            UnitText::new("québioctet", "québioctets", "Qio"),
+        ],
+    )
+    .with_plural_rule(PluralRule::French);
+
+    /// German, whose nouns are capitalized even in the middle of a sentence and whose
+    /// prefixed units don't change in the plural.
+    pub const DE: &'static UnitNames = &UnitNames::new(
+        "de",
+        [
+            UnitText::new("Byte", "Bytes", "B"),
+            UnitText::new("Kilobyte", "Kilobyte", "kB"),
+            UnitText::new("Kibibyte", "Kibibyte", "KiB"),
+            UnitText::new("Megabyte", "Megabyte", "MB"),
+            UnitText::new("Mebibyte", "Mebibyte", "MiB"),
+            UnitText::new("Gigabyte", "Gigabyte", "GB"),
+            UnitText::new("Gibibyte", "Gibibyte", "GiB"),
+            UnitText::new("Terabyte", "Terabyte", "TB"),
+            UnitText::new("Tebibyte", "Tebibyte", "TiB"),
+            UnitText::new("Petabyte", "Petabyte", "PB"),
+            UnitText::new("Pebibyte", "Pebibyte", "PiB"),
+            UnitText::new("Exabyte", "Exabyte", "EB"),
+            UnitText::new("Exbibyte", "Exbibyte", "EiB"),
+            UnitText::new("Zettabyte", "Zettabyte", "ZB"),
+            UnitText::new("Zebibyte", "Zebibyte", "ZiB"),
+            UnitText::new("Yottabyte", "Yottabyte", "YB"),
+            UnitText::new("Yobibyte", "Yobibyte", "YiB"),
+            UnitText::new("Ronnabyte", "Ronnabyte", "RB"),
+            UnitText::new("Robibyte", "Robibyte", "RiB"),
+            UnitText::new("Quettabyte", "Quettabyte", "QB"),
+            UnitText::new("Quebibyte", "Quebibyte", "QiB"),
+        ],
+    );
+
+    /// Russian, which declines the names for a few of a unit: "3 байта", but "512 байт".
+    pub const RU: &'static UnitNames = &UnitNames::new(
+        "ru",
+        [
+            UnitText::new("байт", "байт", "Б").with_paucal("байта"),
+            UnitText::new("килобайт", "килобайт", "кБ").with_paucal("килобайта"),
+            UnitText::new("кибибайт", "кибибайт", "КиБ").with_paucal("кибибайта"),
+            UnitText::new("мегабайт", "мегабайт", "МБ").with_paucal("мегабайта"),
+            UnitText::new("мебибайт", "мебибайт", "МиБ").with_paucal("мебибайта"),
+            UnitText::new("гигабайт", "гигабайт", "ГБ").with_paucal("гигабайта"),
+            UnitText::new("гибибайт", "гибибайт", "ГиБ").with_paucal("гибибайта"),
+            UnitText::new("терабайт", "терабайт", "ТБ").with_paucal("терабайта"),
+            UnitText::new("тебибайт", "тебибайт", "ТиБ").with_paucal("тебибайта"),
+            UnitText::new("петабайт", "петабайт", "ПБ").with_paucal("петабайта"),
+            UnitText::new("пебибайт", "пебибайт", "ПиБ").with_paucal("пебибайта"),
+            UnitText::new("эксабайт", "эксабайт", "ЭБ").with_paucal("эксабайта"),
+            UnitText::new("эксбибайт", "эксбибайт", "ЭиБ").with_paucal("эксбибайта"),
+            UnitText::new("зеттабайт", "зеттабайт", "ЗБ").with_paucal("зеттабайта"),
+            UnitText::new("зебибайт", "зебибайт", "ЗиБ").with_paucal("зебибайта"),
+            UnitText::new("йоттабайт", "йоттабайт", "ЙБ").with_paucal("йоттабайта"),
+            UnitText::new("йобибайт", "йобибайт", "ЙиБ").with_paucal("йобибайта"),
+            UnitText::new("роннабайт", "роннабайт", "РБ").with_paucal("роннабайта"),
+            UnitText::new("робибайт", "робибайт", "РиБ").with_paucal("робибайта"),
+            UnitText::new("кветтабайт", "кветтабайт", "КвБ").with_paucal("кветтабайта"),
+            UnitText::new("квебибайт", "квебибайт", "КвиБ").with_paucal("квебибайта"),
+        ],
+    )
+    .with_plural_rule(PluralRule::Russian);
+
+    /// Japanese, which spells the names in katakana and uses the English symbols.
+    pub const JA: &'static UnitNames = &UnitNames::new(
+        "ja",
+        [
+            UnitText::new("バイト", "バイト", "B"),
+            UnitText::new("キロバイト", "キロバイト", "KB"),
+            UnitText::new("キビバイト", "キビバイト", "KiB"),
+            UnitText::new("メガバイト", "メガバイト", "MB"),
+            UnitText::new("メビバイト", "メビバイト", "MiB"),
+            UnitText::new("ギガバイト", "ギガバイト", "GB"),
+            UnitText::new("ギビバイト", "ギビバイト", "GiB"),
+            UnitText::new("テラバイト", "テラバイト", "TB"),
+            UnitText::new("テビバイト", "テビバイト", "TiB"),
+            UnitText::new("ペタバイト", "ペタバイト", "PB"),
+            UnitText::new("ペビバイト", "ペビバイト", "PiB"),
+            UnitText::new("エクサバイト", "エクサバイト", "EB"),
+            UnitText::new("エクスビバイト", "エクスビバイト", "EiB"),
+            UnitText::new("ゼタバイト", "ゼタバイト", "ZB"),
+            UnitText::new("ゼビバイト", "ゼビバイト", "ZiB"),
+            UnitText::new("ヨタバイト", "ヨタバイト", "YB"),
+            UnitText::new("ヨビバイト", "ヨビバイト", "YiB"),
+            UnitText::new("ロナバイト", "ロナバイト", "RB"),
+            UnitText::new("ロビバイト", "ロビバイト", "RiB"),
+            UnitText::new("クエタバイト", "クエタバイト", "QB"),
+            UnitText::new("クエビバイト", "クエビバイト", "QiB"),
+        ],
+    );
+
+    const BUILT_IN: [&'static UnitNames; 5] = [
+        UnitNames::EN,
+        UnitNames::FR,
+        UnitNames::DE,
+        UnitNames::RU,
+        UnitNames::JA,
+    ];
+
+    /// Creates a vocabulary for `language` (a tag such as `fr` or `pt-BR`), with the words
+    /// for each unit in the order of [`Unit::ALL`] and the [`PluralRule::English`].
+    pub const fn new(language: &'static str, units: [UnitText; 21]) -> Self {
+        UnitNames {
+            language,
+            units,
+            plural_rule: PluralRule::English,
+        }
+    }
+
+    /// Sets how the form of a name is picked for a number.
+    pub const fn with_plural_rule(self, plural_rule: PluralRule) -> Self {
+        UnitNames {
+            plural_rule,
+            ..self
+        }
+    }
+
+    /// Returns a copy of the vocabulary for another language tag, e.g. to adjust a
+    /// built-in vocabulary for a regional variant.
+    pub const fn with_language(self, language: &'static str) -> Self {
+        UnitNames { language, ..self }
+    }
+
+    /// Replaces the words for `unit`.
+    pub const fn with_unit(mut self, unit: Unit, text: UnitText) -> Self {
+        self.units[index(unit)] = text;
+        self
+    }
+
+    /// Returns the language tag of the vocabulary.
+    pub const fn language(&self) -> &'static str {
+        self.language
+    }
+
+    /// Returns the words for `unit`.
+    pub const fn text(&self, unit: Unit) -> &UnitText {
+        &self.units[index(unit)]
+    }
+
+    /// Returns how the form of a name is picked for a number.
+    pub const fn plural_rule(&self) -> PluralRule {
+        self.plural_rule
+    }
+
+    /// Makes `names` available to [`UnitNames::for_language()`], in place of any
+    /// vocabulary registered or built in for the same language before.
+    pub fn register(names: &'static UnitNames) {
+        let mut registered = REGISTERED.write().unwrap_or_else(PoisonError::into_inner);
+        registered.retain(|other| !other.language.eq_ignore_ascii_case(names.language));
+        registered.push(names);
+    }
+
+    /// Removes `names` from the registered vocabularies again, so that tests leave the
+    /// registry the way they found it.
+    #[cfg(test)]
+    pub(crate) fn unregister(names: &'static UnitNames) {
+        let mut registered = REGISTERED.write().unwrap_or_else(PoisonError::into_inner);
+        registered.retain(|other| !std::ptr::eq(*other, names));
+    }
+
+    /// Returns the vocabulary for a language tag, ignoring case and falling back to the
+    /// language without its region or script, so that `fr-CA` finds [`UnitNames::FR`]
+    /// unless a vocabulary was registered for `fr-CA` itself.
+    pub fn for_language(language: &str) -> Option<&'static UnitNames> {
+        let registered = REGISTERED.read().unwrap_or_else(PoisonError::into_inner);
+        let find = |language: &str| {
+            let matches =
+                |names: &&&'static UnitNames| names.language.eq_ignore_ascii_case(language);
+            registered
+                .iter()
+                .rev()
+                .find(matches)
+                .or_else(|| UnitNames::BUILT_IN.iter().find(matches))
+                .copied()
+        };
+        find(language).or_else(|| match language.find(['-', '_']) {
+            Some(end) => find(&language[..end]),
+            None => None,
+        })
+    }
+
+    /// Writes the name or symbol of `unit` in `style`, in the form that goes with a number
+    /// whose whole part is `whole`, and which has decimal places if `fraction` is set.
+    pub(crate) fn format(
+        &self,
+        fmt: &mut dyn Write,
+        unit: Unit,
+        (whole, fraction): (u128, bool),
+        style: &Style,
+    ) -> fmt::Result {
+        let text = self.text(unit);
+        let name = self.plural_rule.pick(text, whole, fraction);
+        match style {
+            Style::Smart => match unit {
+                Unit::Byte => fmt.write_str(name),
+                _ => fmt.write_str(text.symbol),
+            },
+            Style::FullLowerCase => write_lowercase(fmt, name),
+            Style::Full => {
+                let mut chars = name.chars();
+                if let Some(first) = chars.next() {
+                    for c in first.to_uppercase() {
+                        fmt.write_char(c)?;
+                    }
+                }
+                fmt.write_str(chars.as_str())
+            }
+            Style::AbbreviatedLowerCase => write_lowercase(fmt, text.symbol),
+            Style::Abbreviated => fmt.write_str(text.symbol),
+        }
+    }
+
+    /// Matches the longest name or symbol of a unit at the start of `input`, returning the
+    /// unit and the length of the match. Names are matched in any case, while symbols must
+    /// be written as they are or in lowercase, since their case can tell units apart.
+    pub(crate) fn read(&self, input: &[u8]) -> Option<(Unit, usize)> {
+        let input = match str::from_utf8(input) {
+            Ok(input) => input,
+            Err(err) => str::from_utf8(&input[..err.valid_up_to()]).unwrap(),
+        };
+        let any_case = |c: char, expected: char| c.to_lowercase().eq(expected.to_lowercase());
+        let exact = |c: char, expected: char| c == expected;
+        let lowercase = |c: char, expected: char| expected.to_lowercase().eq(Some(c));
+        let mut longest = None;
+        for (&unit, text) in Unit::ALL.iter().zip(self.units.iter()) {
+            let matches = [
+                match_chars(input, text.singular, any_case),
+                match_chars(input, text.plural, any_case),
+                match_chars(input, text.paucal, any_case),
+                match_chars(input, text.symbol, exact),
+                match_chars(input, text.symbol, lowercase),
+            ];
+            for &len in matches.iter().flatten() {
+                if longest.is_none_or(|(_, longest)| len > longest) {
+                    longest = Some((unit, len));
+                }
+            }
+        }
+        longest
+    }
+}
+
+/// Returns the position of `unit` in [`Unit::ALL`].
+const fn index(unit: Unit) -> usize {
+    match unit.base() {
+        None => 0,
+        Some(Base::Base10) => 2 * unit.exponent() as usize - 1,
+        Some(_) => 2 * unit.exponent() as usize,
+    }
+}
+
+fn write_lowercase(fmt: &mut dyn Write, text: &str) -> fmt::Result {
+    match text.chars().any(char::is_uppercase) {
+        false => fmt.write_str(text),
+        true => text
+            .chars()
+            .flat_map(char::to_lowercase)
+            .try_for_each(|c| fmt.write_char(c)),
+    }
+}
+
+/// Returns the length of the text at the start of `input` whose characters are the same
+/// as those of `word` by `same`.
+fn match_chars(input: &str, word: &str, same: impl Fn(char, char) -> bool) -> Option<usize> {
+    let mut len = 0;
+    let mut chars = input.chars();
+    for expected in word.chars() {
+        let c = chars.next()?;
+        if !same(c, expected) {
+            return None;
+        }
+        len += c.len_utf8();
+    }
+    Some(len)
+}
@@ -1,129 +1,443 @@
-//! Implementations of basic arithmetic operations on `Size<T>`.
+//! Implementations of basic arithmetic operations on `Size`.
 //! Only operations that make sense are implemented, e.g. while it is OK to add
 //! two `Size` objects, it does not make sense to multiply them. Meanwhile,
 //! `17MiB / 2` is perfectly rational, but `12KB + 14` isn't (the RHS unit isn't
-//! defined).
+//! defined). Subtracting two sizes can go below zero, so it returns a signed
+//! [`SizeDelta`], which can be added back to a `Size`. Dividing a size by another returns
+//! their ratio as an `f64`, while the remainder is a size again.
+//!
+//! Multiplying and dividing a size panics if the result is negative or too large, like
+//! adding a [`SizeDelta`] does. For byte counts that must not panic, `Size` has
+//! `checked_*`, `saturating_*`, `wrapping_*` and `overflowing_*` methods for each
+//! operation, which treat a negative result as out of range the same way as one that is
+//! too large.
 
-use crate::Size;
-use core::ops::{Add, Div, Mul};
+use crate::{Size, SizeDelta};
+use core::ops::{Add, Div, Mul, Rem, Sub};
 use num_traits::ToPrimitive;
+use std::convert::TryFrom;
 
-impl<T, U> Add<&Size<U>> for &Size<T>
-where
-    T: ToPrimitive,
-    U: ToPrimitive,
-{
-    type Output = Size<u64>;
+/// Generates the `checked_*`, `saturating_*`, `wrapping_*` and `overflowing_*` methods for
+/// an operation, from a method returning its [`Outcome`].
+macro_rules! arithmetic_family {
+    ($outcome:ident$(<$U:ident>)?($arg:ident: $ty:ty), $op:literal,
+     $checked:ident, $saturating:ident, $wrapping:ident, $overflowing:ident) => {
+        #[doc = concat!("Returns ", $op, ", or `None` if the result is negative, larger ")]
+        #[doc = "than `u128::MAX` bytes or not a number at all."]
+        pub fn $checked$(<$U: ToPrimitive>)?(self, $arg: $ty) -> Option<Size> {
+            self.$outcome($arg).checked()
+        }
+
+        #[doc = concat!("Returns ", $op, ", clamped to the range of a `u128` byte count. ")]
+        #[doc = "A result that isn't a number at all is zero, like when casting NaN."]
+        pub fn $saturating$(<$U: ToPrimitive>)?(self, $arg: $ty) -> Size {
+            self.$outcome($arg).saturating()
+        }
+
+        #[doc = concat!("Returns ", $op, ", wrapped around modulo 2^128 bytes. A result ")]
+        #[doc = "that isn't a number at all is zero."]
+        pub fn $wrapping$(<$U: ToPrimitive>)?(self, $arg: $ty) -> Size {
+            self.$outcome($arg).overflowing().0
+        }
+
+        #[doc = concat!("Returns ", $op, ", wrapped around modulo 2^128 bytes, along with ")]
+        #[doc = "whether it was out of range."]
+        pub fn $overflowing$(<$U: ToPrimitive>)?(self, $arg: $ty) -> (Size, bool) {
+            self.$outcome($arg).overflowing()
+        }
+    };
+}
+
+impl Size {
+    arithmetic_family!(
+        add_outcome(other: Size),
+        "the sum of the two sizes",
+        checked_add,
+        saturating_add,
+        wrapping_add,
+        overflowing_add
+    );
+
+    arithmetic_family!(
+        mul_outcome<U>(factor: U),
+        "the size multiplied by `factor`",
+        checked_mul,
+        saturating_mul,
+        wrapping_mul,
+        overflowing_mul
+    );
+
+    arithmetic_family!(
+        div_outcome<U>(divisor: U),
+        "the size divided by `divisor` (infinitely large if it is zero and the size isn't)",
+        checked_div,
+        saturating_div,
+        wrapping_div,
+        overflowing_div
+    );
+
+    fn add_outcome(self, other: Size) -> Outcome {
+        match self.bytes().overflowing_add(other.bytes()) {
+            (sum, false) => Outcome::Fits(sum),
+            (sum, true) => Outcome::TooLarge(sum),
+        }
+    }
+
+    fn mul_outcome<U: ToPrimitive>(self, factor: U) -> Outcome {
+        let bytes = self.bytes();
+        match Scalar::new(&factor) {
+            Scalar::Integer {
+                negative,
+                magnitude,
+            } => {
+                let (product, overflow) = bytes.overflowing_mul(magnitude);
+                Outcome::from_magnitude(negative, product, overflow)
+            }
+            Scalar::Float(factor) => Outcome::from_f64(bytes as f64 * factor),
+        }
+    }
+
+    /// Divides the size. Division by zero goes the way of floating point division: a
+    /// non-zero size becomes infinitely large and zero bytes isn't a number.
+    fn div_outcome<U: ToPrimitive>(self, divisor: U) -> Outcome {
+        let bytes = self.bytes();
+        match Scalar::new(&divisor) {
+            Scalar::Integer { magnitude: 0, .. } => Outcome::from_f64(bytes as f64 / 0.0),
+            Scalar::Integer {
+                negative,
+                magnitude,
+            } => Outcome::from_magnitude(negative, bytes / magnitude, false),
+            Scalar::Float(divisor) => Outcome::from_f64(bytes as f64 / divisor),
+        }
+    }
+
+    /// Returns how many times `other` fits in the size, e.g. how many whole 4 KiB blocks
+    /// a file fills.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` is zero bytes.
+    pub fn div_floor(self, other: Size) -> u128 {
+        self.bytes() / other.bytes()
+    }
+
+    /// Returns how many of `other` it takes to hold the size, e.g. how many 4 KiB blocks a
+    /// file needs.
+    ///
+    /// ```
+    /// use size::Size;
+    ///
+    /// let file = Size::from_kib(10);
+    /// assert_eq!(file.div_floor(Size::from_kib(4)), 2);
+    /// assert_eq!(file.div_ceil(Size::from_kib(4)), 3);
+    /// assert_eq!(file % Size::from_kib(4), Size::from_kib(2));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` is zero bytes.
+    pub fn div_ceil(self, other: Size) -> u128 {
+        self.bytes().div_ceil(other.bytes())
+    }
 
-    fn add(self, other: &Size<U>) -> Self::Output {
-        Size::Bytes(self.bytes() + other.bytes())
+    /// Returns whether the sizes are at most `tolerance` apart.
+    ///
+    /// ```
+    /// use size::Size;
+    ///
+    /// let size = Size::from_mb(1);
+    /// assert!(size.approx_eq(Size::from_mib(1), Size::from_kb(50)));
+    /// assert!(!size.approx_eq(Size::from_mib(1), Size::from_kb(40)));
+    /// assert!(size.approx_eq_relative(Size::from_mib(1), 0.05));
+    /// ```
+    pub fn approx_eq(self, other: Size, tolerance: Size) -> bool {
+        self.bytes().abs_diff(other.bytes()) <= tolerance.bytes()
+    }
+
+    /// Returns whether the sizes are at most `ratio` times the larger of the two apart,
+    /// e.g. within 1% of each other for a `ratio` of `0.01`.
+    pub fn approx_eq_relative(self, other: Size, ratio: f64) -> bool {
+        let (bytes, other) = (self.bytes(), other.bytes());
+        bytes.abs_diff(other) as f64 <= ratio * bytes.max(other) as f64
     }
 }
 
-impl<T, U> Add<Size<U>> for &Size<T>
-where
-    T: ToPrimitive,
-    U: ToPrimitive,
-{
-    type Output = Size<u64>;
+/// A number a size is multiplied or divided by, or the value of a size. Integral scalars
+/// (including floating point ones with an integral value) are applied exactly, and are
+/// kept as a sign and magnitude so that all of `i128` and `u128` fit.
+pub(crate) enum Scalar {
+    Integer { negative: bool, magnitude: u128 },
+    Float(f64),
+}
 
-    fn add(self, other: Size<U>) -> Self::Output {
-        Size::Bytes(self.bytes() + other.bytes())
+impl Scalar {
+    pub(crate) fn new<U: ToPrimitive>(scalar: &U) -> Self {
+        let integer = match scalar.to_i128() {
+            Some(integer) => Some((integer < 0, integer.unsigned_abs())),
+            None => scalar.to_u128().map(|integer| (false, integer)),
+        };
+        match (integer, scalar.to_f64()) {
+            (Some((negative, magnitude)), Some(float)) if magnitude as f64 == float.abs() => {
+                Scalar::Integer {
+                    negative,
+                    magnitude,
+                }
+            }
+            (_, Some(float)) => Scalar::Float(float),
+            (Some((negative, magnitude)), None) => Scalar::Integer {
+                negative,
+                magnitude,
+            },
+            (None, None) => Scalar::Float(f64::NAN),
+        }
     }
 }
 
-impl<T, U> Add<&Size<U>> for Size<T>
-where
-    T: ToPrimitive,
-    U: ToPrimitive,
-{
-    type Output = Size<u64>;
+/// The number of bytes an operation results in, depending on whether it fits in a `u128`.
+/// Results out of range carry their value modulo 2^128.
+enum Outcome {
+    Fits(u128),
+    Negative(u128),
+    TooLarge(u128),
+    /// The result isn't a number at all, e.g. after multiplying by NaN.
+    Invalid,
+}
 
-    fn add(self, other: &Size<U>) -> Self::Output {
-        Size::Bytes(self.bytes() + other.bytes())
+impl Outcome {
+    /// Classifies a result given its magnitude modulo 2^128, whether it is negative and
+    /// whether the magnitude overflowed.
+    fn from_magnitude(negative: bool, magnitude: u128, overflow: bool) -> Self {
+        match (negative, overflow) {
+            (false, false) => Outcome::Fits(magnitude),
+            (false, true) => Outcome::TooLarge(magnitude),
+            // Minus zero is still zero, unless the magnitude merely wrapped around to it
+            (true, false) if magnitude == 0 => Outcome::Fits(0),
+            (true, _) => Outcome::Negative(magnitude.wrapping_neg()),
+        }
+    }
+
+    /// Classifies `bytes` after truncating it towards zero, like casting it would.
+    fn from_f64(bytes: f64) -> Self {
+        // 2^128, which is exactly representable as an `f64`
+        const WRAP: f64 = 340_282_366_920_938_463_463_374_607_431_768_211_456.0;
+        let bytes = bytes.trunc();
+        // `%` is exact on floating point numbers, and infinities wrap around to zero
+        let wrapped = |bytes: f64| match bytes.is_finite() {
+            true => (bytes % WRAP) as u128,
+            false => 0,
+        };
+        if bytes.is_nan() {
+            Outcome::Invalid
+        } else if bytes < 0.0 {
+            Outcome::Negative(wrapped(-bytes).wrapping_neg())
+        } else if bytes >= WRAP {
+            Outcome::TooLarge(wrapped(bytes))
+        } else {
+            Outcome::Fits(bytes as u128)
+        }
+    }
+
+    fn checked(self) -> Option<Size> {
+        match self {
+            Outcome::Fits(bytes) => Some(Size::from_bytes(bytes)),
+            _ => None,
+        }
+    }
+
+    fn saturating(self) -> Size {
+        match self {
+            Outcome::Fits(bytes) => Size::from_bytes(bytes),
+            Outcome::Negative(_) | Outcome::Invalid => Size::ZERO,
+            Outcome::TooLarge(_) => Size::MAX,
+        }
+    }
+
+    fn overflowing(self) -> (Size, bool) {
+        match self {
+            Outcome::Fits(bytes) => (Size::from_bytes(bytes), false),
+            Outcome::Negative(bytes) | Outcome::TooLarge(bytes) => (Size::from_bytes(bytes), true),
+            Outcome::Invalid => (Size::ZERO, true),
+        }
     }
 }
 
-impl<T, U> Add<Size<U>> for Size<T>
-where
-    T: ToPrimitive,
-    U: ToPrimitive,
-{
-    type Output = Size<u64>;
+/// Implements an operator between two sizes for borrowed operands, too.
+macro_rules! forward_ref_binop {
+    (impl $imp:ident, $method:ident) => {
+        impl $imp<&Size> for Size {
+            type Output = <Size as $imp<Size>>::Output;
+
+            fn $method(self, other: &Size) -> Self::Output {
+                $imp::$method(self, *other)
+            }
+        }
+
+        impl $imp<Size> for &Size {
+            type Output = <Size as $imp<Size>>::Output;
+
+            fn $method(self, other: Size) -> Self::Output {
+                $imp::$method(*self, other)
+            }
+        }
+
+        impl $imp<&Size> for &Size {
+            type Output = <Size as $imp<Size>>::Output;
+
+            fn $method(self, other: &Size) -> Self::Output {
+                $imp::$method(*self, *other)
+            }
+        }
+    };
+}
+
+impl Add<Size> for Size {
+    type Output = Size;
+
+    fn add(self, other: Size) -> Self::Output {
+        Size::from_bytes(self.bytes() + other.bytes())
+    }
+}
+
+forward_ref_binop!(impl Add, add);
+
+/// Subtracts two sizes, panicking if their difference doesn't fit in the `i128` of a
+/// [`SizeDelta`], which takes sizes of more than 2^127 bytes.
+impl Sub<Size> for Size {
+    type Output = SizeDelta;
+
+    fn sub(self, other: Size) -> Self::Output {
+        let (bytes, other) = (self.bytes(), other.bytes());
+        let delta = match bytes >= other {
+            true => i128::try_from(bytes - other).ok(),
+            false => 0i128.checked_sub_unsigned(other - bytes),
+        };
+        SizeDelta::from_bytes(delta.expect("attempt to subtract with overflow"))
+    }
+}
+
+forward_ref_binop!(impl Sub, sub);
+
+/// Adds a delta to a size, panicking if the result is negative or more than `u128::MAX`
+/// bytes.
+impl Add<SizeDelta> for Size {
+    type Output = Size;
+
+    fn add(self, other: SizeDelta) -> Self::Output {
+        let (bytes, delta) = (self.bytes(), other.bytes());
+        let sum = match delta >= 0 {
+            true => bytes.checked_add(delta.unsigned_abs()),
+            false => bytes.checked_sub(delta.unsigned_abs()),
+        };
+        Size::from_bytes(sum.expect("attempt to add with overflow"))
+    }
+}
+
+impl Add<SizeDelta> for &Size {
+    type Output = Size;
+
+    fn add(self, other: SizeDelta) -> Self::Output {
+        *self + other
+    }
+}
 
-    fn add(self, other: Size<U>) -> Self::Output {
-        Size::Bytes(self.bytes() + other.bytes())
+impl Add<Size> for SizeDelta {
+    type Output = Size;
+
+    fn add(self, other: Size) -> Self::Output {
+        other + self
     }
 }
 
-impl<T, U> Mul<U> for Size<T>
+/// Multiplies a size, panicking if the result is negative, more than `u128::MAX` bytes or
+/// not a number.
+impl<U> Mul<U> for Size
 where
-    T: ToPrimitive,
     U: ToPrimitive,
 {
-    type Output = Size<u64>;
+    type Output = Size;
 
     fn mul(self, other: U) -> Self::Output {
-        Size::Bytes((self.bytes() as f64 * other.to_f64().unwrap()) as u64)
+        self.checked_mul(other)
+            .expect("attempt to multiply with overflow")
     }
 }
 
-impl<T, U> Mul<U> for &Size<T>
+impl<U> Mul<U> for &Size
 where
-    T: ToPrimitive,
     U: ToPrimitive,
 {
-    type Output = Size<u64>;
+    type Output = Size;
 
     fn mul(self, other: U) -> Self::Output {
-        Size::Bytes((self.bytes() as f64 * other.to_f64().unwrap()) as u64)
+        *self * other
     }
 }
 
-/// Defined to allow multiplying an untyped number by a Size<T>, because
+/// Defined to allow multiplying an untyped number by a Size, because
 /// multiplication should be commutative.
-impl<T> Mul<Size<T>> for i64
-where
-    T: ToPrimitive,
-{
-    type Output = Size<u64>;
+impl Mul<Size> for i64 {
+    type Output = Size;
 
-    fn mul(self, other: Size<T>) -> Self::Output {
-        Size::Bytes((self as u64 * other.bytes()) as u64)
+    fn mul(self, other: Size) -> Self::Output {
+        other * self
     }
 }
 
-impl<T> Mul<Size<T>> for f64
-where
-    T: ToPrimitive,
-{
-    type Output = Size<u64>;
+impl Mul<Size> for f64 {
+    type Output = Size;
 
-    fn mul(self, other: Size<T>) -> Self::Output {
-        Size::Bytes((self * other.bytes() as f64) as u64)
+    fn mul(self, other: Size) -> Self::Output {
+        other * self
     }
 }
 
-impl<T, U> Div<U> for &Size<T>
+/// Divides a size, panicking on division by zero like integer division does, and if the
+/// result is negative or more than `u128::MAX` bytes.
+impl<U> Div<U> for Size
 where
-    T: ToPrimitive,
     U: ToPrimitive,
 {
-    type Output = Size<u64>;
+    type Output = Size;
 
     fn div(self, other: U) -> Self::Output {
-        Size::Bytes((self.bytes() as f64 / other.to_f64().unwrap()) as u64)
+        match Scalar::new(&other) {
+            Scalar::Integer { magnitude: 0, .. } | Scalar::Float(0.0) => {
+                panic!("attempt to divide by zero")
+            }
+            _ => self
+                .checked_div(other)
+                .expect("attempt to divide with overflow"),
+        }
     }
 }
 
-impl<T, U> Div<U> for Size<T>
+impl<U> Div<U> for &Size
 where
-    T: ToPrimitive,
     U: ToPrimitive,
 {
-    type Output = Size<u64>;
+    type Output = Size;
 
     fn div(self, other: U) -> Self::Output {
-        Size::Bytes((self.bytes() as f64 / other.to_f64().unwrap()) as u64)
+        *self / other
     }
 }
+
+impl Div<Size> for Size {
+    type Output = f64;
+
+    fn div(self, other: Size) -> Self::Output {
+        self.bytes() as f64 / other.bytes() as f64
+    }
+}
+
+forward_ref_binop!(impl Div, div);
+
+impl Rem<Size> for Size {
+    type Output = Size;
+
+    fn rem(self, other: Size) -> Self::Output {
+        Size::from_bytes(self.bytes() % other.bytes())
+    }
+}
+
+forward_ref_binop!(impl Rem, rem);
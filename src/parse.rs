@@ -1,373 +1,1386 @@
-use crate::Unit;
+//! A hand-written lexer for sizes such as `"2.50 MiB"` or `"42kb"`. It works on raw bytes,
+//! so it doesn't need the input to be valid UTF-8, and it never allocates unless parsing
+//! fails.
+//!
+//! The grammar is a number, an optional unit and nothing else, with any number of spaces
+//! before, between and after them. Numbers match `[0-9.]+([eE][-+]?[0-9]+)?` and units are
+//! recognized case-sensitively, e.g. `b`, `B`, `byte(s)`, `Byte(s)` for bytes and `k`, `kb`,
+//! `K`, `KB`, `kilobyte(s)`, `Kilobyte(s)`, `ki`, `kib`, `Ki`, `KiB`, `kibibyte(s)` and
+//! `Kibibyte(s)` for the (binary) kilo prefix, and likewise for the `m`, `g`, `t`, `p`, `e`,
+//! `z`, `y`, `r` and `q` prefixes up to quettabytes and quebibytes. Like most lexers, each
+//! token is the longest match at its position, so `1e3` is one number while `1eb` is one
+//! followed by exabytes.
+//! That is how [`ParseMode::Lenient`] reads units, while [`ParseMode::Strict`] only takes
+//! SI and IEC symbols such as `kB` and `KiB` at their exact case, and reads `b`, `bit(s)`
+//! and names like `kilobit(s)` as bits.
+//! A [`SizeDelta`] may also start with a sign: `+`, `-` or the Unicode minus sign `−`.
+//! With [`SizeParser::with_lenient_input()`], any Unicode whitespace counts as a space and
+//! numbers may group their digits, e.g. `1,024` or `1_000_000`. A [`Locale`] set with
+//! [`SizeParser::with_locale()`] replaces the decimal point and may group digits too.
+//! Units may also be named in another language with [`SizeParser::with_unit_names()`].
+
+use crate::{Base, Locale, RoundingMode, Size, SizeDelta, Style, TypedSize, Unit, UnitNames};
+use num_traits::FromPrimitive;
+use std::cmp::Ordering;
 use std::error;
+use std::ffi::OsStr;
 use std::fmt;
-use std::num::ParseFloatError;
+use std::ops::Range;
+use std::str::{self, FromStr};
+
+/// The error returned when a size can't be parsed. It records where in the input the
+/// problem is, what was found there and what was expected instead.
+///
+/// The regular `Display` output is a single line, while the alternate form (`{:#}`)
+/// follows it with the input and a caret under the offending text:
+///
+/// ```
+/// use size::Size;
+/// use std::str::FromStr;
+///
+/// let err = Size::from_str("2.5 Mbi").unwrap_err();
+/// assert_eq!(err.found(), "Mbi");
+/// assert_eq!(err.suggestion(), Some("MiB"));
+/// assert_eq!(
+///     format!("{:#}", err),
+///     "expected a unit, found `Mbi` at offset 4 (did you mean `MiB`?)\n\
+///      2.5 Mbi\n    ^^^"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    span: Range<usize>,
+    found: String,
+    suggestion: Option<&'static str>,
+    input: String,
+    /// The position of the offending text in characters rather than bytes.
+    column: usize,
+}
 
-pub enum Token {
-    Unit(Unit),
+/// The reason a size couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// Something other than what was expected was found, or the input ended early.
+    Unexpected(Expected),
+    /// The number matched the grammar but isn't valid, e.g. `1.2.3`.
+    InvalidNumber,
+    /// The number can't be represented by the size's numeric type, e.g. `300` for a
+    /// `TypedSize<u8>`, or the size has more than `u128::MAX` bytes.
+    OutOfRange,
+    /// The size isn't a whole number of bytes, e.g. `0.3 KiB`, and the parser was set up
+    /// to reject those with [`FractionalBytes::Reject`].
+    FractionalBytes,
+    /// The unit's case doesn't say which unit it is by SI and IEC conventions, e.g. `KB`,
+    /// `mb` or a bare `M`, and the parser was set up to read units with
+    /// [`ParseMode::Strict`].
+    AmbiguousUnit,
+}
+
+/// The part of a size the parser was looking for when it failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
     Number,
+    Unit,
+    End,
+    /// The name of a [`Base`], when parsing one on its own.
+    Base,
+    /// The name of a [`Style`], when parsing one on its own.
+    Style,
 }
 
-#[derive(Debug)]
-pub enum ScanError {
-    TokenError(Error),
-    ParseError(ParseFloatError),
+impl ParseError {
+    /// Returns the reason parsing failed.
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+
+    /// Returns the byte range of the offending text in the input. It is empty when the
+    /// input ended too early.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the offending text, or an empty string when the input ended too early.
+    pub fn found(&self) -> &str {
+        &self.found
+    }
+
+    /// Returns the abbreviation of the unit the offending text most likely meant, if any.
+    pub fn suggestion(&self) -> Option<&'static str> {
+        self.suggestion
+    }
 }
 
-impl fmt::Display for ScanError {
+impl fmt::Display for Expected {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            ScanError::TokenError(err) => err.fmt(f),
-            ScanError::ParseError(err) => err.fmt(f),
+            Expected::Number => f.write_str("a number"),
+            Expected::Unit => f.write_str("a unit"),
+            Expected::End => f.write_str("end of input"),
+            Expected::Base => f.write_str("a base"),
+            Expected::Style => f.write_str("a style"),
         }
     }
 }
 
-impl error::Error for ScanError {
-    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-        match self {
-            ScanError::TokenError(err) => Some(err),
-            ScanError::ParseError(err) => Some(err),
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::Unexpected(expected) if self.found.is_empty() => {
+                write!(f, "expected {}, found end of input", expected)?
+            }
+            ParseErrorKind::Unexpected(expected) => write!(
+                f,
+                "expected {}, found `{}` at offset {}",
+                expected, self.found, self.span.start
+            )?,
+            ParseErrorKind::InvalidNumber => write!(
+                f,
+                "invalid number `{}` at offset {}",
+                self.found, self.span.start
+            )?,
+            ParseErrorKind::OutOfRange => write!(
+                f,
+                "value `{}` at offset {} is out of range for the target type",
+                self.found, self.span.start
+            )?,
+            ParseErrorKind::FractionalBytes => write!(
+                f,
+                "`{}` at offset {} is not a whole number of bytes",
+                self.found, self.span.start
+            )?,
+            ParseErrorKind::AmbiguousUnit => write!(
+                f,
+                "ambiguous unit `{}` at offset {}",
+                self.found, self.span.start
+            )?,
+        }
+        if let Some(suggestion) = self.suggestion {
+            write!(f, " (did you mean `{}`?)", suggestion)?;
         }
+        if f.alternate() {
+            let width = self.found.chars().count().max(1);
+            write!(f, "\n{}\n{:2$}", self.input, "", self.column)?;
+            for _ in 0..width {
+                f.write_str("^")?;
+            }
+        }
+        Ok(())
     }
 }
 
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+impl error::Error for ParseError {}
+
+/// What to do with a size that isn't a whole number of bytes, such as `0.3 KiB`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FractionalBytes {
+    /// Round the size to a whole number of bytes.
+    Round(RoundingMode),
+    /// Fail with [`ParseErrorKind::FractionalBytes`].
+    Reject,
+}
+
+/// How strictly a [`SizeParser`] reads the case of unit abbreviations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Accept abbreviations in any case as bytes, as long as the trailing b is in the same
+    /// case as the prefix letter, e.g. `kb`, `KB` and `KiB` but not `kB`. Bits are never
+    /// accepted. This is how `FromStr` reads sizes.
+    Lenient,
+    /// Read abbreviations by SI and IEC conventions: `B` is a byte and `b` (or `bit`) is a
+    /// bit, `k` is kilo while `M`, `G` and larger prefixes are uppercase, and base-two
+    /// prefixes are spelled like `Ki`. Anything else, including a bare prefix, fails with
+    /// [`ParseErrorKind::AmbiguousUnit`] and suggests the symbol for the same unit in
+    /// bytes, or in bits when the trailing b is lowercase. Sizes that aren't a whole number
+    /// of bytes fail too, unless [`SizeParser::with_fractional_bytes()`] is set.
+    Strict,
+}
+
+/// Parses sizes from text with configurable options. `Size::from_str()`,
+/// [`Size::parse_bytes()`] and [`Size::parse_os_str()`] are shorthands for parsing with
+/// the default options.
+///
+/// Sizes are computed exactly from their decimal digits, without going through `f64`, and
+/// rounded to a whole number of bytes:
+///
+/// ```
+/// use size::{FractionalBytes, ParseErrorKind, RoundingMode, Size, SizeParser};
+///
+/// let parser = SizeParser::new();
+/// assert_eq!(parser.parse("1.5 KiB").unwrap(), Size::from_bytes(1536));
+/// assert_eq!(parser.parse("0.3 KiB").unwrap(), Size::from_bytes(307));
+///
+/// let parser = SizeParser::new().with_fractional_bytes(FractionalBytes::Reject);
+/// let err = parser.parse("0.3 KiB").unwrap_err();
+/// assert_eq!(err.kind(), &ParseErrorKind::FractionalBytes);
+///
+/// let parser = parser.with_fractional_bytes(FractionalBytes::Round(RoundingMode::Ceil));
+/// assert_eq!(parser.parse("0.3 KiB").unwrap(), Size::from_bytes(308));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SizeParser {
+    /// `None` until set, which rounds in [`ParseMode::Lenient`] and rejects in
+    /// [`ParseMode::Strict`].
+    fractional_bytes: Option<FractionalBytes>,
+    jedec_units: bool,
+    mode: ParseMode,
+    lenient_input: bool,
+    locale: Locale,
+    unit_names: Option<&'static UnitNames>,
+}
+
+impl SizeParser {
+    /// Creates a parser that rounds sizes to the nearest whole byte, with ties rounded to
+    /// even, and reads base-ten units as such.
+    pub fn new() -> Self {
+        SizeParser {
+            fractional_bytes: None,
+            jedec_units: false,
+            mode: ParseMode::Lenient,
+            lenient_input: false,
+            locale: Locale::new(),
+            unit_names: None,
+        }
+    }
+
+    /// Sets what happens to sizes that aren't a whole number of bytes. Unless this is
+    /// set, they are rounded to the nearest byte with ties rounded to even, or rejected in
+    /// [`ParseMode::Strict`].
+    pub fn with_fractional_bytes(self, fractional_bytes: FractionalBytes) -> Self {
+        SizeParser {
+            fractional_bytes: Some(fractional_bytes),
+            ..self
+        }
+    }
+
+    /// Sets whether base-ten units are read as their base-two counterparts, the way JEDEC
+    /// defines them, so that `1 KB` (or `1 kilobyte`) is 1024 bytes. Base-two units such
+    /// as `KiB` mean the same either way.
+    ///
+    /// ```
+    /// use size::{Size, SizeParser};
+    ///
+    /// let parser = SizeParser::new().with_jedec_units(true);
+    /// assert_eq!(parser.parse("4 GB").unwrap(), Size::from_gib(4));
+    /// assert_eq!(parser.parse("4 GiB").unwrap(), Size::from_gib(4));
+    /// ```
+    pub fn with_jedec_units(self, jedec_units: bool) -> Self {
+        SizeParser {
+            jedec_units,
+            ..self
+        }
+    }
+
+    /// Sets how strictly the case of unit abbreviations is read. Sizes counted in bits are
+    /// converted to bytes, so a number of bits that isn't a multiple of eight isn't a whole
+    /// number of bytes. Such sizes are rejected in [`ParseMode::Strict`] unless
+    /// [`SizeParser::with_fractional_bytes()`] opts into rounding them.
+    ///
+    /// ```
+    /// use size::{FractionalBytes, ParseErrorKind, ParseMode, RoundingMode, Size, SizeParser};
+    ///
+    /// let parser = SizeParser::new().with_mode(ParseMode::Strict);
+    /// assert_eq!(parser.parse("100 Mb").unwrap(), Size::from_bytes(12_500_000));
+    /// assert_eq!(parser.parse("100 MB").unwrap(), Size::from_mb(100));
+    ///
+    /// let err = parser.parse("100 mb").unwrap_err();
+    /// assert_eq!(err.kind(), &ParseErrorKind::AmbiguousUnit);
+    /// assert_eq!(err.suggestion(), Some("Mb"));
+    ///
+    /// let err = parser.parse("1 b").unwrap_err();
+    /// assert_eq!(err.kind(), &ParseErrorKind::FractionalBytes);
+    /// let parser = parser.with_fractional_bytes(FractionalBytes::Round(RoundingMode::Ceil));
+    /// assert_eq!(parser.parse("1 b").unwrap(), Size::from_bytes(1));
+    /// ```
+    pub fn with_mode(self, mode: ParseMode) -> Self {
+        SizeParser { mode, ..self }
+    }
+
+    /// Sets whether to accept input as it is often pasted from spreadsheets and other
+    /// programs: any Unicode whitespace around the number and the unit (such as a tab or a
+    /// no-break space), and digits grouped in threes by commas, underscores, apostrophes,
+    /// or no-break or thin spaces. Grouping is only allowed before the decimal point, and
+    /// a number whose groups aren't three digits long, e.g. `1,5`, is invalid rather than
+    /// read without its separators.
+    ///
+    /// ```
+    /// use size::{ParseErrorKind, Size, SizeParser};
+    ///
+    /// let parser = SizeParser::new().with_lenient_input(true);
+    /// assert_eq!(parser.parse("1,024 KB").unwrap(), Size::from_kb(1024));
+    /// assert_eq!(parser.parse("4\u{a0}GiB").unwrap(), Size::from_gib(4));
+    /// assert_eq!(parser.parse("1_000_000 B").unwrap(), Size::from_mb(1));
+    /// assert_eq!(parser.parse("2.5\tMB").unwrap(), Size::from_kb(2500));
+    ///
+    /// let err = parser.parse("1,5 MB").unwrap_err();
+    /// assert_eq!(err.kind(), &ParseErrorKind::InvalidNumber);
+    /// ```
+    pub fn with_lenient_input(self, lenient_input: bool) -> Self {
+        SizeParser {
+            lenient_input,
+            ..self
+        }
+    }
+
+    /// Reads numbers with the decimal separator of `locale`, and accepts digits grouped
+    /// by its grouping separator. As with lenient input, the groups must be as long as the
+    /// locale's, so that e.g. `1.5 MB` is an invalid number rather than 15 megabytes with
+    /// [`Locale::DE`]. The locale's unit separator may come between the number and the unit.
+    ///
+    /// ```
+    /// use size::{Locale, Size, SizeParser};
+    ///
+    /// let parser = SizeParser::new().with_locale(Locale::FR);
+    /// assert_eq!(parser.parse("2,5 MiB").unwrap(), Size::from_kib(2560));
+    /// assert_eq!(parser.parse("1\u{202f}024\u{a0}KiB").unwrap(), Size::from_mib(1));
+    /// assert!(parser.parse("2.5 MiB").is_err());
+    /// ```
+    pub fn with_locale(self, locale: Locale) -> Self {
+        SizeParser { locale, ..self }
+    }
+
+    /// Accepts the names and symbols of units in another language, in any case, besides
+    /// the English ones. Units that read as English are still read as such, so with
+    /// [`ParseMode::Strict`] a symbol like `KB` is ambiguous in every language.
+    ///
+    /// ```
+    /// use size::{Locale, Size, SizeParser, UnitNames};
+    ///
+    /// let parser = SizeParser::new().with_unit_names(UnitNames::FR).with_locale(Locale::FR);
+    /// assert_eq!(parser.parse("2,5 Mo").unwrap(), Size::from_kb(2500));
+    /// assert_eq!(parser.parse("3 kibioctets").unwrap(), Size::from_kib(3));
+    /// assert_eq!(parser.parse("3 KiB").unwrap(), Size::from_kib(3));
+    /// ```
+    pub fn with_unit_names(self, unit_names: &'static UnitNames) -> Self {
+        SizeParser {
+            unit_names: Some(unit_names),
+            ..self
+        }
+    }
+
+    /// Parses a size from a string.
+    pub fn parse(&self, input: &str) -> Result<Size, ParseError> {
+        self.parse_bytes(input.as_bytes())
+    }
+
+    /// Parses a size from bytes that need not be valid UTF-8. The input is read like
+    /// [`SizeParser::parse()`] reads a string, including non-ASCII unit names, separators
+    /// and whitespace where the parser allows them, and invalid UTF-8 is rejected.
+    pub fn parse_bytes(&self, input: &[u8]) -> Result<Size, ParseError> {
+        let (_, bytes) = self.read_bytes(input, false)?;
+        Ok(Size::from_bytes(bytes))
+    }
+
+    /// Parses a size from an `OsStr`, such as a command line argument, without requiring
+    /// it to be valid Unicode.
+    pub fn parse_os_str(&self, input: &OsStr) -> Result<Size, ParseError> {
+        self.parse_bytes(input.as_encoded_bytes())
+    }
+
+    /// Parses a size into a [`TypedSize`] of any numeric type. A number that is whole in
+    /// its own unit keeps that unit, anything else is converted to a whole number of bytes.
+    ///
+    /// ```
+    /// use size::{SizeParser, TypedSize};
+    ///
+    /// let parser = SizeParser::new();
+    /// assert!(matches!(parser.parse_typed::<u8>("2.0 KiB"), Ok(TypedSize::Kibibytes(2))));
+    /// assert!(matches!(parser.parse_typed::<u16>("1.5 KiB"), Ok(TypedSize::Bytes(1536))));
+    /// assert!(parser.parse_typed::<u8>("1.5 KiB").is_err());
+    /// ```
+    pub fn parse_typed<T: FromPrimitive>(&self, input: &str) -> Result<TypedSize<T>, ParseError> {
+        let mut lex = self.lexer(input.as_bytes());
+        let number = lex.read_number()?;
+        let (unit, bits) = self.read_unit(&mut lex)?;
+        let end = lex.position;
+        lex.read_eof()?;
+        self.convert(&lex, number, unit, bits, end, false)
+    }
+
+    /// Parses a signed size difference such as `-5 MB`, where a size without a sign is
+    /// positive. Rounding to whole bytes applies to the signed value, so
+    /// [`RoundingMode::Floor`] rounds negative deltas away from zero.
+    ///
+    /// ```
+    /// use size::{SizeDelta, SizeParser};
+    ///
+    /// let delta = SizeParser::new().parse_delta("-1.5 KiB").unwrap();
+    /// assert_eq!(delta, SizeDelta::from_bytes(-1536));
+    /// ```
+    pub fn parse_delta(&self, input: &str) -> Result<SizeDelta, ParseError> {
+        let (negative, bytes) = self.read_bytes(input.as_bytes(), true)?;
+        // Wrapping only matters for -2^127, whose magnitude is out of range as an `i128`
+        Ok(SizeDelta::from_bytes(match negative {
+            true => (bytes as i128).wrapping_neg(),
+            false => bytes as i128,
+        }))
+    }
+
+    /// Reads a number of bytes, after a sign if `signed` is set, and returns whether it is
+    /// negative along with its magnitude. Signed sizes are limited to the range of an
+    /// `i128`, as they are read into a [`SizeDelta`].
+    fn read_bytes(&self, input: &[u8], signed: bool) -> Result<(bool, u128), ParseError> {
+        let mut lex = self.lexer(input);
+        let negative = signed && lex.read_sign();
+        let number = lex.read_number()?;
+        let span = number.span.clone();
+        let (unit, bits) = self.read_unit(&mut lex)?;
+        let end = lex.position;
+        lex.read_eof()?;
+        let size: TypedSize<u128> = self.convert(&lex, number, unit, bits, end, negative)?;
+        let limit = match (signed, negative) {
+            (false, _) => u128::MAX,
+            (true, false) => i128::MAX.unsigned_abs(),
+            (true, true) => i128::MIN.unsigned_abs(),
+        };
+        let bytes = size
+            .try_bytes()
+            .ok()
+            .filter(|&bytes| bytes <= limit)
+            .ok_or_else(|| lex.error(ParseErrorKind::OutOfRange, span, None))?;
+        Ok((negative, bytes))
+    }
+
+    fn lexer<'a>(&self, input: &'a [u8]) -> Lexer<'a> {
+        Lexer {
+            lenient_input: self.lenient_input,
+            locale: self.locale,
+            unit_names: self.unit_names,
+            ..Lexer::new(input)
+        }
+    }
+
+    /// Reads the unit after a number, which is bytes if there is none, and whether it
+    /// counts bits rather than bytes.
+    fn read_unit(&self, lex: &mut Lexer) -> Result<(Unit, bool), ParseError> {
+        let (unit, bits) = match self.mode {
+            ParseMode::Lenient => (lex.read_unit()?.unwrap_or(Unit::Byte), false),
+            ParseMode::Strict => match lex.read_spelling()? {
+                Some((spelling, start)) => spelling.strict(self.jedec_units).map_err(|symbol| {
+                    let kind = ParseErrorKind::AmbiguousUnit;
+                    lex.error(kind, start..lex.position, Some(symbol))
+                })?,
+                None => (Unit::Byte, false),
+            },
+        };
+        match (self.jedec_units, unit.base()) {
+            (true, Some(Base::Base10)) => Ok((unit.counterpart(), bits)),
+            _ => Ok((unit, bits)),
+        }
+    }
+
+    /// Turns `number` followed by `unit` (which ends at `end`) into a `TypedSize<T>`, rounding
+    /// it as the magnitude of a negative number if `negative` is set. If `bits` is set, the
+    /// number counts bits of `unit` rather than bytes.
+    fn convert<T: FromPrimitive>(
+        &self,
+        lex: &Lexer,
+        number: Number,
+        unit: Unit,
+        bits: bool,
+        end: usize,
+        negative: bool,
+    ) -> Result<TypedSize<T>, ParseError> {
+        let Number {
+            mut digits,
+            mut exponent,
+            inexact,
+            span,
+        } = number;
+        let out_of_range = || lex.error(ParseErrorKind::OutOfRange, span.clone(), None);
+
+        // A bit is an eighth of a byte, or 125 thousandths
+        if bits {
+            digits = digits.checked_mul_add(125, 0).ok_or_else(out_of_range)?;
+            exponent = exponent.saturating_sub(3);
+        }
+
+        if digits.to_u128() == Some(0) {
+            exponent = 0;
+        }
+        if !inexact {
+            while exponent < 0 && digits.div_rem(10).1 == 0 {
+                digits = digits.div_rem(10).0;
+                exponent += 1;
+            }
+        }
+
+        // A whole number of its own unit, e.g. `2.0 KiB`, is kept in that unit
+        if exponent >= 0 {
+            let mut value = Some(digits);
+            // Multiplying anything but zero by 10^78 overflows
+            for _ in 0..exponent.min(78) {
+                value = value.and_then(|value| value.checked_mul_add(10, 0));
+            }
+            let value = value.and_then(Wide::to_u128).and_then(T::from_u128);
+            return value
+                .map(|value| TypedSize::from_unit(value, unit))
+                .ok_or_else(out_of_range);
+        }
+
+        // Anything else is converted to bytes, dividing by ten one digit at a time to know
+        // which side of one half the fractional part of the byte count is on
+        // Multiplying by the unit one prefix at a time keeps every factor within a `u64`
+        let step = match unit.base() {
+            Some(Base::Base2) => 1024,
+            _ => 1000,
+        };
+        let mut bytes = Some(digits);
+        for _ in 0..unit.exponent() {
+            bytes = bytes.and_then(|bytes| bytes.checked_mul_add(step, 0));
+        }
+        let bytes = bytes.ok_or_else(out_of_range)?;
+        let (bytes, fraction, zero) = match exponent.unsigned_abs() {
+            // Even 2^256 is less than 10^78, so the byte count rounds down to zero
+            scale if scale > 78 => (0, Ordering::Less, false),
+            scale => {
+                let (mut bytes, mut first, mut rest) = (bytes, 0, inexact);
+                for _ in 0..scale {
+                    rest |= first != 0;
+                    let (quotient, remainder) = bytes.div_rem(10);
+                    bytes = quotient;
+                    first = remainder;
+                }
+                let fraction = match first.cmp(&5) {
+                    Ordering::Equal if rest => Ordering::Greater,
+                    fraction => fraction,
+                };
+                let bytes = bytes.to_u128().ok_or_else(out_of_range)?;
+                (bytes, fraction, first == 0 && !rest)
+            }
+        };
+
+        let fractional_bytes = match (self.fractional_bytes, self.mode) {
+            (Some(fractional_bytes), _) => fractional_bytes,
+            (None, ParseMode::Lenient) => FractionalBytes::Round(RoundingMode::HalfEven),
+            (None, ParseMode::Strict) => FractionalBytes::Reject,
+        };
+        let bytes = match fractional_bytes {
+            _ if zero => bytes,
+            FractionalBytes::Round(rounding) => {
+                let rounding = match negative {
+                    true => rounding.mirrored(),
+                    false => rounding,
+                };
+                let round_up = rounding.rounds_up(bytes % 2 == 1, fraction, zero);
+                bytes
+                    .checked_add(round_up as u128)
+                    .ok_or_else(out_of_range)?
+            }
+            FractionalBytes::Reject => {
+                let kind = ParseErrorKind::FractionalBytes;
+                return Err(lex.error(kind, span.start..end, None));
+            }
+        };
+        T::from_u128(bytes)
+            .map(TypedSize::Bytes)
+            .ok_or_else(out_of_range)
+    }
+}
+
+impl Default for SizeParser {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl error::Error for Error {}
+impl FromStr for Unit {
+    type Err = ParseError;
 
+    /// Parses a unit on its own, spelled any way it can be in a size, e.g. `KiB`, `kb` or
+    /// `Megabytes`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lex = Lexer::new(s.as_bytes());
+        let start = lex.skip_spaces(false);
+        let unit = lex
+            .read_unit()?
+            .ok_or_else(|| lex.unexpected(Expected::Unit, start))?;
+        lex.read_eof()?;
+        Ok(unit)
+    }
+}
+
+impl FromStr for Base {
+    type Err = ParseError;
 
-#[derive(Debug)]
-pub enum Error {
-    EOF,
-    Unmatch,
+    /// Parses `base-2` (or `base2`, `2`, `binary`), `base-10` (or `base10`, `10`,
+    /// `decimal`) or `jedec`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const NAMES: [(&str, Base); 9] = [
+            ("base-2", Base::Base2),
+            ("base2", Base::Base2),
+            ("2", Base::Base2),
+            ("binary", Base::Base2),
+            ("base-10", Base::Base10),
+            ("base10", Base::Base10),
+            ("10", Base::Base10),
+            ("decimal", Base::Base10),
+            ("jedec", Base::Jedec),
+        ];
+        parse_name(s, &NAMES, Expected::Base)
+    }
 }
 
-pub struct Lexer<'a> {
-    cmap: Vec<usize>,
-    start: std::str::Chars<'a>,
-    current: std::str::Chars<'a>,
-    max_len: usize,
+impl FromStr for Style {
+    type Err = ParseError;
 
+    /// Parses the name of a style as it is displayed, e.g. `abbreviated-lowercase`,
+    /// ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const NAMES: [(&str, Style); 5] = [
+            ("abbreviated", Style::Abbreviated),
+            ("abbreviated-lowercase", Style::AbbreviatedLowerCase),
+            ("full", Style::Full),
+            ("smart", Style::Smart),
+            ("full-lowercase", Style::FullLowerCase),
+        ];
+        parse_name(s, &NAMES, Expected::Style)
+    }
+}
 
-    zz_state: usize,
-    zz_lexical_state: usize,
-    zz_marked_pos: usize,
-    zz_current_pos: usize,
-    zz_start_read: usize,
+/// Looks up `input`, minus surrounding spaces, in a table of `names`, ignoring case and
+/// allowing `_` in place of `-`. Suggests the closest name if there's no match.
+fn parse_name<V: Copy>(
+    input: &str,
+    names: &[(&'static str, V)],
+    expected: Expected,
+) -> Result<V, ParseError> {
+    let lex = Lexer::new(input.as_bytes());
+    let start = input.len() - input.trim_start_matches(' ').len();
+    let word = input.trim_matches(' ');
+    let normalized = word.bytes().map(|c| match c.to_ascii_lowercase() {
+        b'_' => b'-',
+        c => c,
+    });
+    if let Some(&(_, value)) = names
+        .iter()
+        .find(|(name, _)| name.bytes().eq(normalized.clone()))
+    {
+        return Ok(value);
+    }
 
-    zz_at_eof: bool,
+    let normalized: Vec<u8> = normalized.collect();
+    let suggestion = names
+        .iter()
+        .map(|&(name, _)| (edit_distance(&normalized, name.as_bytes()), name))
+        .filter(|&(distance, name)| distance <= 2 && distance < name.len())
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, name)| name);
+    let kind = ParseErrorKind::Unexpected(expected);
+    Err(lex.error(kind, start..start + word.len(), suggestion))
+}
 
+/// A number read exactly from its decimal digits, worth `digits * 10^exponent`.
+struct Number {
+    digits: Wide,
+    exponent: i64,
+    /// Whether some non-zero digits were dropped because there were too many of them.
+    inexact: bool,
+    span: Range<usize>,
 }
 
-impl<'a> Lexer<'a> {
-    pub const ZZ_ROW: [usize; 124] = [0, 27, 54, 81, 108, 135, 162, 189, 216, 243, 270, 297, 324, 351, 378, 405, 432, 459, 378, 486, 378, 513, 540, 378, 567, 594, 378, 621, 648, 378, 675, 702, 729, 756, 378, 783, 810, 837, 864, 891, 918, 945, 972, 378, 378, 999, 1026, 378, 1053, 1080, 1053, 1107, 1134, 378, 1161, 1188, 378, 1161, 1215, 1242, 378, 1269, 1269, 1296, 1323, 1350, 1377, 1404, 1431, 1458, 1485, 1512, 1539, 1566, 1593, 1620, 378, 1647, 1674, 1701, 1728, 1755, 1782, 1809, 1836, 1863, 1890, 1917, 1944, 1971, 1998, 2025, 2052, 2079, 2106, 2133, 2160, 2187, 2214, 2241, 2268, 2295, 2322, 2349, 2376, 2403, 2430, 2457, 2484, 2511, 2538, 2565, 2592, 2619, 2646, 2673, 2700, 2727, 2754, 2781, 2808, 2835, 2862, 2889];
-    pub const ZZ_TRANS: [i32; 2916] = [-1, 1, -1, 2, 3, -1, 1, 4, -1, -1, -1, 5, 6, 7, -1, 8, 9, 10, -1, 11, 12, 13, -1, 14, 15, -1, 15, -1, -1, 16, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 17, -1, 18, -1, 19, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 20, -1, 21, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 22, -1, -1, -1, -1, -1, 23, -1, -1, -1, -1, -1, -1, 24, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 23, -1, 25, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 26, -1, -1, 27, -1, -1, -1, 28, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 29, -1, 30, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 27, -1, 26, -1, 31, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 29, -1, -1, -1, -1, -1, -1, 32, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 18, -1, -1, 17, -1, -1, -1, 33, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 34, -1, -1, 35, -1, -1, -1, 36, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 35, -1, 34, -1, 37, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 20, -1, -1, -1, -1, -1, -1, 38, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 22, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 39, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 39, -1, -1, 15, -1, 15, -1, -1, -1, 40, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 41, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 42, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 43, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 44, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 45, -1, -1, -1, -1, -1, -1, -1, 46, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 47, -1, -1, -1, -1, 48, -1, -1, 49, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 50, -1, -1, 49, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 51, -1, -1, -1, -1, -1, -1, 52, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 53, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 54, -1, -1, -1, -1, -1, -1, 55, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 53, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 56, -1, -1, -1, -1, 57, -1, -1, -1, -1, -1, -1, 55, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 43, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 58, -1, -1, 59, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 60, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 60, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 44, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 61, 62, -1, -1, -1, -1, 63, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 64, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 65, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 66, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 67, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 68, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 69, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 70, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 71, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 72, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 73, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 74, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 75, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 62, -1, -1, -1, -1, -1, 76, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 77, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 78, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 79, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 80, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 81, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 82, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 83, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 84, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 85, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 86, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 87, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 88, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 89, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 90, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 91, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 92, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 93, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 94, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 95, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 96, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 97, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 98, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 99, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 100, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 101, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 102, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 103, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 104, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 105, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 106, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 107, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 108, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 109, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 110, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 111, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 112, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 113, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 114, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 115, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 20, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 116, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 117, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 118, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 119, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 120, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 121, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 122, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 123, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 43, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 18, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 44, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 47, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 23, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 53, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 26, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 56, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 29, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 34, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 60, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1];
-    pub const ZZ_ATTR: [i32; 124] = [0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 9, 1, 0, 0, 9, 1, 9, 1, 0, 9, 1, 1, 9, 0, 1, 9, 1, 1, 1, 1, 9, 0, 1, 1, 1, 0, 0, 0, 0, 9, 9, 0, 0, 9, 0, 0, 1, 0, 0, 9, 1, 0, 9, 0, 0, 0, 9, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1];
-    pub const ZZ_ACTION: [i32; 124] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 0, 0, 16, 17, 18, 19, 0, 20, 21, 22, 23, 0, 24, 25, 26, 27, 28, 29, 30, 0, 31, 32, 33, 0, 0, 0, 0, 34, 35, 0, 0, 36, 0, 0, 37, 0, 0, 38, 39, 0, 40, 0, 0, 0, 41, 0, 42, 43, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 44, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 45, 0, 0, 0, 0, 0, 0, 0, 0, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56];
-    pub const ZZ_LEXSTATE: [i32; 2] = [0, 0];
-    pub const YYINITIAL: usize = 0;
-
-
-    pub const YYEOF: i32 = -1;
-
-    pub fn new(input: &'a str) -> Lexer<'a> {
-        let max_len = input.chars().clone().count();
-        let chars = input.chars();
-        let mut cmap: Vec<usize> = Vec::with_capacity(0x110000);
-        cmap.resize(0x110000, 0);
-        cmap[32] = 23;
-        cmap[43] = 25;
-        cmap[45] = 25;
-        cmap[46] = 24;
-        cmap[48] = 26;
-        cmap[49] = 26;
-        cmap[50] = 26;
-        cmap[51] = 26;
-        cmap[52] = 26;
-        cmap[53] = 26;
-        cmap[54] = 26;
-        cmap[55] = 26;
-        cmap[56] = 26;
-        cmap[57] = 26;
-        cmap[66] = 1;
-        cmap[69] = 21;
-        cmap[71] = 16;
-        cmap[75] = 7;
-        cmap[77] = 12;
-        cmap[80] = 19;
-        cmap[84] = 17;
-        cmap[97] = 14;
-        cmap[98] = 6;
-        cmap[101] = 4;
-        cmap[103] = 13;
-        cmap[105] = 8;
-        cmap[107] = 11;
-        cmap[108] = 9;
-        cmap[109] = 15;
-        cmap[111] = 10;
-        cmap[112] = 20;
-        cmap[114] = 18;
-        cmap[115] = 5;
-        cmap[116] = 3;
-        cmap[120] = 22;
-        cmap[121] = 2;
+/// An unsigned 256-bit integer, with just the operations needed to parse numbers exactly.
+/// It is stored as 64-bit limbs, least significant first.
+#[derive(Clone, Copy)]
+struct Wide([u64; 4]);
+
+impl Wide {
+    /// Returns `self * factor + addend`, or `None` if that overflows.
+    fn checked_mul_add(self, factor: u64, addend: u64) -> Option<Wide> {
+        let mut result = [0; 4];
+        let mut carry = addend as u128;
+        for (limb, result) in self.0.iter().zip(result.iter_mut()) {
+            let value = *limb as u128 * factor as u128 + carry;
+            *result = value as u64;
+            carry = value >> 64;
+        }
+        match carry {
+            0 => Some(Wide(result)),
+            _ => None,
+        }
+    }
+
+    /// Returns the quotient and remainder of dividing `self` by `divisor`.
+    fn div_rem(self, divisor: u64) -> (Wide, u64) {
+        let mut result = [0; 4];
+        let mut remainder = 0u128;
+        for (limb, result) in self.0.iter().zip(result.iter_mut()).rev() {
+            let value = remainder << 64 | *limb as u128;
+            *result = (value / divisor as u128) as u64;
+            remainder = value % divisor as u128;
+        }
+        (Wide(result), remainder as u64)
+    }
 
+    fn to_u128(self) -> Option<u128> {
+        match self.0 {
+            [low, high, 0, 0] => Some((high as u128) << 64 | low as u128),
+            _ => None,
+        }
+    }
+}
 
+/// How a unit was written: which prefix letter it used, if it was abbreviated, and what
+/// followed it.
+#[derive(Clone, Copy)]
+struct Spelling {
+    unit: Unit,
+    prefix: Option<u8>,
+    suffix: Suffix,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Suffix {
+    /// The unit was spelled out in full, e.g. `kilobytes` or `kilobits`.
+    Name {
+        bits: bool,
+    },
+    /// A bare prefix, e.g. `k` or `Ki`.
+    None,
+    UpperB,
+    LowerB,
+    /// `bit` or `bits` after a prefix, e.g. `Mbit`.
+    Bit,
+}
+
+impl Spelling {
+    /// Returns the unit if [`ParseMode::Lenient`] accepts this spelling: a name or an
+    /// abbreviation in any case, as long as an abbreviation spells its trailing b in the
+    /// same case as its prefix letter. Bits are never accepted.
+    fn lenient(&self) -> Option<Unit> {
+        let accepted = match (self.prefix, self.suffix) {
+            (_, Suffix::Name { bits }) => !bits,
+            (_, Suffix::None) => true,
+            (_, Suffix::Bit) => false,
+            (None, _) => true,
+            (Some(prefix), Suffix::UpperB) => prefix.is_ascii_uppercase(),
+            (Some(prefix), Suffix::LowerB) => prefix.is_ascii_lowercase(),
+        };
+        match accepted {
+            true => Some(self.unit),
+            false => None,
+        }
+    }
+
+    /// Returns the unit and whether it counts bits if [`ParseMode::Strict`] accepts this
+    /// spelling, or else the symbol it should have been written as. `K` is accepted for
+    /// kilo when `jedec_units` is set, as that is how JEDEC spells it.
+    fn strict(&self, jedec_units: bool) -> Result<(Unit, bool), &'static str> {
+        let bits = match self.suffix {
+            Suffix::Name { bits } => return Ok((self.unit, bits)),
+            Suffix::UpperB => false,
+            Suffix::LowerB | Suffix::Bit => true,
+            // Nothing says whether a bare prefix counts bytes or bits
+            Suffix::None => return Err(si_symbol(self.unit, false)),
+        };
+        let expected = si_symbol(self.unit, false).as_bytes()[0];
+        match self.prefix {
+            Some(b'K') if jedec_units && self.unit == Unit::Kilobyte => Ok((self.unit, bits)),
+            Some(prefix) if prefix != expected => Err(si_symbol(self.unit, bits)),
+            _ => Ok((self.unit, bits)),
+        }
+    }
+}
+
+enum Token<'a> {
+    Unit(Spelling),
+    Number(&'a str),
+    Unknown,
+    End,
+}
+
+struct Lexer<'a> {
+    input: &'a [u8],
+    position: usize,
+    /// Whether any Unicode whitespace separates tokens and numbers may group their digits,
+    /// as set with [`SizeParser::with_lenient_input()`].
+    lenient_input: bool,
+    locale: Locale,
+    /// Names and symbols of units in another language to accept besides the English ones.
+    unit_names: Option<&'static UnitNames>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a [u8]) -> Lexer<'a> {
         Lexer {
-            cmap,
-            start: chars.clone(),
-            current: chars.clone(),
+            input,
+            position: 0,
+            lenient_input: false,
+            locale: Locale::new(),
+            unit_names: None,
+        }
+    }
 
-            max_len,
-            zz_state: 0,
-            zz_lexical_state: Lexer::YYINITIAL,
-            zz_marked_pos: 0,
-            zz_current_pos: 0,
-            zz_start_read: 0,
+    fn read_number(&mut self) -> Result<Number, ParseError> {
+        let start = self.skip_spaces(false);
+        let text = match self.next_token() {
+            Token::Number(text) => text,
+            _ => return Err(self.unexpected(Expected::Number, start)),
+        };
+        let span = start..start + text.len();
+        let invalid = || self.error(ParseErrorKind::InvalidNumber, span.clone(), None);
 
-            zz_at_eof: false,
+        let mantissa = text.find(['e', 'E']).unwrap_or(text.len());
+        let mut number = Number {
+            digits: Wide([0; 4]),
+            exponent: 0,
+            inexact: false,
+            span: span.clone(),
+        };
+        let decimal = self.locale.decimal_separator();
+        let mut point = false;
+        // Grouped digits come in groups of the locale's size (or threes) after the first
+        // group, all split by the same separator and only before the decimal point
+        let size = self.locale.grouping().map_or(3, |(_, size)| size);
+        let mut separator = None;
+        let mut group = 0;
+        let grouped = |separator: Option<char>, group| separator.is_none() || group == size;
+        for c in text[..mantissa].chars() {
+            match c {
+                c if c == decimal && (point || !grouped(separator, group)) => return Err(invalid()),
+                c if c == decimal => {
+                    point = true;
+                    continue;
+                }
+                '0'..='9' => group += 1,
+                _ => {
+                    let first = match separator {
+                        None => (1..=size).contains(&group),
+                        Some(separator) => separator == c && group == size,
+                    };
+                    if point || !first {
+                        return Err(invalid());
+                    }
+                    separator = Some(c);
+                    group = 0;
+                    continue;
+                }
+            }
+            let digit = c.to_digit(10).unwrap() as u64;
+            match number.digits.checked_mul_add(10, digit) {
+                Some(digits) => {
+                    number.digits = digits;
+                    number.exponent -= point as i64;
+                }
+                // Past 77 digits, keep the magnitude but not the digits themselves
+                None => {
+                    number.exponent += !point as i64;
+                    number.inexact |= digit != 0;
+                }
+            }
+        }
+        let empty = !text[..mantissa].contains(|c: char| c.is_ascii_digit());
+        if empty || !(point || grouped(separator, group)) {
+            return Err(invalid());
+        }
 
+        if let Some((_, exponent)) = text.as_bytes().split_at(mantissa).1.split_first() {
+            let (negative, exponent) = match exponent.split_first() {
+                Some((b'-', exponent)) => (true, exponent),
+                Some((b'+', exponent)) => (false, exponent),
+                _ => (false, exponent),
+            };
+            let exponent = exponent.iter().fold(0i64, |exponent, c| {
+                exponent
+                    .saturating_mul(10)
+                    .saturating_add((c - b'0') as i64)
+            });
+            number.exponent = match negative {
+                true => number.exponent.saturating_sub(exponent),
+                false => number.exponent.saturating_add(exponent),
+            };
         }
+        Ok(number)
     }
 
-    pub fn yytext(&self) -> String {
-        let len = self.zz_marked_pos - self.zz_start_read;
-        let mut text = String::with_capacity(len);
-        let mut chars = self.start.clone();
+    /// Reads the unit as it is spelled, along with where it starts, returning `None` if the
+    /// input ends instead.
+    fn read_spelling(&mut self) -> Result<Option<(Spelling, usize)>, ParseError> {
+        let start = self.skip_spaces(true);
+        match self.next_token() {
+            // A unit cut short by more letters is a misspelling, not a unit followed by junk
+            Token::Unit(spelling) if self.found_len(start) == self.position - start => {
+                Ok(Some((spelling, start)))
+            }
+            Token::End => Ok(None),
+            _ => Err(self.unexpected(Expected::Unit, start)),
+        }
+    }
 
-        for _ in 0..len {
-            text.push(match chars.next() { Some(c) => c, _ => break,});
+    /// Reads a unit spelled the way [`ParseMode::Lenient`] accepts it, returning `None` if
+    /// the input ends instead.
+    fn read_unit(&mut self) -> Result<Option<Unit>, ParseError> {
+        match self.read_spelling()? {
+            Some((spelling, start)) => match spelling.lenient() {
+                Some(unit) => Ok(Some(unit)),
+                None => Err(self.unexpected(Expected::Unit, start)),
+            },
+            None => Ok(None),
         }
-        text
     }
 
-    pub fn yylex(&mut self) -> Result<Token, Error> {
-        let mut zz_input: i32;
+    /// Reads an optional sign, returning whether it is negative.
+    fn read_sign(&mut self) -> bool {
+        let start = self.skip_spaces(false);
+        let rest = &self.input[start..];
+        let (negative, len) = match rest.first() {
+            Some(b'+') => (false, 1),
+            Some(b'-') => (true, 1),
+            _ if rest.starts_with("−".as_bytes()) => (true, "−".len()),
+            _ => (false, 0),
+        };
+        self.position += len;
+        negative
+    }
+
+    fn read_eof(&mut self) -> Result<(), ParseError> {
+        let start = self.skip_spaces(false);
+        match self.next_token() {
+            Token::End => Ok(()),
+            _ => Err(self.unexpected(Expected::End, start)),
+        }
+    }
 
-        // cached
+    /// Skips spaces, returning where the next token starts. Before a unit, the characters
+    /// of the locale's unit separator count as spaces too, so that sizes are read back the
+    /// way the locale prints them.
+    fn skip_spaces(&mut self, before_unit: bool) -> usize {
         loop {
-            let mut zz_marked_pos_l = self.zz_marked_pos;
-            let mut zz_action = -1;
-            let mut zz_current_pos_l = self.zz_marked_pos;
-            let mut current = self.current.clone();
-            
-
-            self.zz_start_read = self.zz_marked_pos;
-            self.zz_current_pos = self.zz_marked_pos;
-            self.start = self.current.clone();
-
-            self.zz_state = Lexer::ZZ_LEXSTATE[self.zz_lexical_state] as usize;
-
-            // set up zz_action for empty match case:
-            let zz_attributes = Lexer::ZZ_ATTR[self.zz_state];
-            if (zz_attributes & 1) == 1 {
-                zz_action = self.zz_state as i32;
-            }
+            let len = match decode(&self.input[self.position..]) {
+                Some((' ', len)) => len,
+                Some((c, len)) if self.lenient_input && c.is_whitespace() => len,
+                Some((c, len)) if before_unit && self.locale.unit_separator().contains(c) => len,
+                _ => return self.position,
+            };
+            self.position += len;
+        }
+    }
 
-            'zz_for_action: loop {
-                if zz_current_pos_l < self.max_len {
-                    zz_input = current.next().unwrap() as i32;
-                    zz_current_pos_l += 1;
-                } else if self.zz_at_eof {
-                    zz_input = Lexer::YYEOF;
-                    break 'zz_for_action;
-                } else {
-                    self.zz_current_pos = zz_current_pos_l;
-
-                    if self.max_len <= zz_current_pos_l {
-                        zz_input = Lexer::YYEOF;
-                        break 'zz_for_action;
-                    } else {
-                        zz_input = current.next().unwrap() as i32;
-                        zz_current_pos_l += 1;
-                    }
+    fn next_token(&mut self) -> Token<'a> {
+        let start = self.position;
+        let (token, len) = match self.input.get(start) {
+            None => (Token::End, 0),
+            Some(_) if self.starts_number(start) => {
+                let len = self.number_len(start);
+                // The number is made up of ASCII digits and whole separator characters
+                let text = str::from_utf8(&self.input[start..start + len]).unwrap();
+                (Token::Number(text), len)
+            }
+            // English units come first, unless they are only part of a word, e.g. `Mo` in
+            // French, or spelled in a way only a localized symbol allows, e.g. `kB` in German
+            Some(_) => match (self.unit(start), self.localized_unit(start)) {
+                (Some((spelling, len)), _)
+                    if len == self.found_len(start) && spelling.lenient().is_some() =>
+                {
+                    (Token::Unit(spelling), len)
                 }
+                (_, Some((spelling, len))) => (Token::Unit(spelling), len),
+                (Some((spelling, len)), None) => (Token::Unit(spelling), len),
+                (None, None) => (Token::Unknown, 0),
+            },
+        };
+        self.position += len;
+        token
+    }
 
-                let idx = Lexer::ZZ_ROW[self.zz_state] + self.cmap[zz_input as usize];
-                let zz_next = Lexer::ZZ_TRANS[idx];
-                if zz_next == -1 {
-                    break 'zz_for_action;
-                }
-                self.zz_state = zz_next as usize;
-
-                let zz_attributes = Lexer::ZZ_ATTR[self.zz_state];
-                if (zz_attributes & 1) == 1 {
-                    zz_action = self.zz_state as i32;
-                    zz_marked_pos_l = zz_current_pos_l;
-                    self.current = current.clone();
-                    if (zz_attributes & 8) == 8 {
-                        break 'zz_for_action;
-                    }
+    /// Returns whether a number starts at `start`, with a digit or a decimal separator.
+    fn starts_number(&self, start: usize) -> bool {
+        match decode(&self.input[start..]) {
+            Some((c, _)) => c.is_ascii_digit() || c == self.locale.decimal_separator(),
+            None => false,
+        }
+    }
+
+    /// Matches `[0-9.]+([eE][-+]?[0-9]+)?` at `start`, returning its length, where `.` is the
+    /// locale's decimal separator. The digits may also be grouped by the locale's grouping
+    /// separator, or with lenient input by any of the [`is_separator()`] characters.
+    fn number_len(&self, start: usize) -> usize {
+        let rest = &self.input[start..];
+        let decimal = self.locale.decimal_separator();
+        let mut len = 0;
+        while let Some((c, width)) = decode(&rest[len..]) {
+            let separator = match self.locale.grouping() {
+                Some((separator, _)) if separator == c => true,
+                _ => self.lenient_input && is_separator(c) && c != decimal,
+            };
+            if separator {
+                // A separator only groups digits when it is between two of them, so that
+                // e.g. `4\u{a0}GiB` is a number followed by a space
+                let before = len > 0 && rest[len - 1].is_ascii_digit();
+                let after = rest.get(len + width).is_some_and(u8::is_ascii_digit);
+                if !(before && after) {
+                    break;
                 }
-            }   // loop 'zz_for_action
+            } else if !(c.is_ascii_digit() || c == decimal) {
+                break;
+            }
+            len += width;
+        }
+        if let Some(b'e') | Some(b'E') = rest.get(len) {
+            let sign = match rest.get(len + 1) {
+                Some(b'-') | Some(b'+') => 1,
+                _ => 0,
+            };
+            let digits = count(&rest[len + 1 + sign..], |c| c.is_ascii_digit());
+            if digits > 0 {
+                len += 1 + sign + digits;
+            }
+        }
+        len
+    }
 
-            // store back cached position
-            self.zz_marked_pos = zz_marked_pos_l;
+    /// Matches the longest unit name or abbreviation at `start`, however its letters are
+    /// cased, along with names and symbols for bits. Whether the spelling is accepted is
+    /// up to the [`ParseMode`].
+    fn unit(&self, start: usize) -> Option<(Spelling, usize)> {
+        let rest = &self.input[start..];
+        let first = rest[0];
+        let letter = first.to_ascii_lowercase();
+        let tail = &rest[1..];
+        let plural = |len: usize| match rest.get(len) {
+            Some(b's') => len + 1,
+            _ => len,
+        };
+        let name = |unit, bits| Spelling {
+            unit,
+            prefix: None,
+            suffix: Suffix::Name { bits },
+        };
 
-            if zz_input == Lexer::YYEOF && self.zz_start_read == self.zz_current_pos {
-                self.zz_at_eof = true;
+        if letter == b'b' {
+            if tail.starts_with(b"yte") {
+                return Some((name(Unit::Byte, false), plural(4)));
+            }
+            if tail.starts_with(b"it") {
+                return Some((name(Unit::Byte, true), plural(3)));
+            }
+            let suffix = match first {
+                b'B' => Suffix::UpperB,
+                _ => Suffix::LowerB,
+            };
+            let spelling = Spelling {
+                unit: Unit::Byte,
+                prefix: None,
+                suffix,
+            };
+            return Some((spelling, 1));
+        }
 
-                return Err(Error::EOF);
-            } else {
-                let action = if zz_action < 0 {
-                    zz_action
-                } else {
-                    Lexer::ZZ_ACTION[zz_action as usize]
-                };
-                match action {
-                    1 => { return Ok(Token::Unit(Unit::Byte)); }
-                    57 => { /* nothing */ }
-                    2 => { return Ok(Token::Unit(Unit::Terabyte)); }
-                    58 => { /* nothing */ }
-                    3 => { return Ok(Token::Unit(Unit::Exabyte)); }
-                    59 => { /* nothing */ }
-                    4 => { return Ok(Token::Unit(Unit::Kilobyte)); }
-                    60 => { /* nothing */ }
-                    5 => { return Ok(Token::Unit(Unit::Kilobyte)); }
-                    61 => { /* nothing */ }
-                    6 => { return Ok(Token::Unit(Unit::Megabyte)); }
-                    62 => { /* nothing */ }
-                    7 => { return Ok(Token::Unit(Unit::Gigabyte)); }
-                    63 => { /* nothing */ }
-                    8 => { return Ok(Token::Unit(Unit::Megabyte)); }
-                    64 => { /* nothing */ }
-                    9 => { return Ok(Token::Unit(Unit::Gigabyte)); }
-                    65 => { /* nothing */ }
-                    10 => { return Ok(Token::Unit(Unit::Terabyte)); }
-                    66 => { /* nothing */ }
-                    11 => { return Ok(Token::Unit(Unit::Petabyte)); }
-                    67 => { /* nothing */ }
-                    12 => { return Ok(Token::Unit(Unit::Petabyte)); }
-                    68 => { /* nothing */ }
-                    13 => { return Ok(Token::Unit(Unit::Exabyte)); }
-                    69 => { /* nothing */ }
-                    14 => { /* Skip whitespace */ }
-                    70 => { /* nothing */ }
-                    15 => { return Ok(Token::Number); }
-                    71 => { /* nothing */ }
-                    16 => { return Ok(Token::Unit(Unit::Terabyte)); }
-                    72 => { /* nothing */ }
-                    17 => { return Ok(Token::Unit(Unit::Tebibyte)); }
-                    73 => { /* nothing */ }
-                    18 => { return Ok(Token::Unit(Unit::Exabyte)); }
-                    74 => { /* nothing */ }
-                    19 => { return Ok(Token::Unit(Unit::Exbibyte)); }
-                    75 => { /* nothing */ }
-                    20 => { return Ok(Token::Unit(Unit::Kilobyte)); }
-                    76 => { /* nothing */ }
-                    21 => { return Ok(Token::Unit(Unit::Kibibyte)); }
-                    77 => { /* nothing */ }
-                    22 => { return Ok(Token::Unit(Unit::Kibibyte)); }
-                    78 => { /* nothing */ }
-                    23 => { return Ok(Token::Unit(Unit::Megabyte)); }
-                    79 => { /* nothing */ }
-                    24 => { return Ok(Token::Unit(Unit::Mebibyte)); }
-                    80 => { /* nothing */ }
-                    25 => { return Ok(Token::Unit(Unit::Gigabyte)); }
-                    81 => { /* nothing */ }
-                    26 => { return Ok(Token::Unit(Unit::Gibibyte)); }
-                    82 => { /* nothing */ }
-                    27 => { return Ok(Token::Unit(Unit::Mebibyte)); }
-                    83 => { /* nothing */ }
-                    28 => { return Ok(Token::Unit(Unit::Gibibyte)); }
-                    84 => { /* nothing */ }
-                    29 => { return Ok(Token::Unit(Unit::Tebibyte)); }
-                    85 => { /* nothing */ }
-                    30 => { return Ok(Token::Unit(Unit::Petabyte)); }
-                    86 => { /* nothing */ }
-                    31 => { return Ok(Token::Unit(Unit::Pebibyte)); }
-                    87 => { /* nothing */ }
-                    32 => { return Ok(Token::Unit(Unit::Pebibyte)); }
-                    88 => { /* nothing */ }
-                    33 => { return Ok(Token::Unit(Unit::Exbibyte)); }
-                    89 => { /* nothing */ }
-                    34 => { return Ok(Token::Unit(Unit::Tebibyte)); }
-                    90 => { /* nothing */ }
-                    35 => { return Ok(Token::Unit(Unit::Exbibyte)); }
-                    91 => { /* nothing */ }
-                    36 => { return Ok(Token::Unit(Unit::Kibibyte)); }
-                    92 => { /* nothing */ }
-                    37 => { return Ok(Token::Unit(Unit::Kibibyte)); }
-                    93 => { /* nothing */ }
-                    38 => { return Ok(Token::Unit(Unit::Mebibyte)); }
-                    94 => { /* nothing */ }
-                    39 => { return Ok(Token::Unit(Unit::Gibibyte)); }
-                    95 => { /* nothing */ }
-                    40 => { return Ok(Token::Unit(Unit::Gibibyte)); }
-                    96 => { /* nothing */ }
-                    41 => { return Ok(Token::Unit(Unit::Pebibyte)); }
-                    97 => { /* nothing */ }
-                    42 => { return Ok(Token::Number); }
-                    98 => { /* nothing */ }
-                    43 => { return Ok(Token::Unit(Unit::Byte)); }
-                    99 => { /* nothing */ }
-                    44 => { return Ok(Token::Unit(Unit::Byte)); }
-                    100 => { /* nothing */ }
-                    45 => { return Ok(Token::Unit(Unit::Exabyte)); }
-                    101 => { /* nothing */ }
-                    46 => { return Ok(Token::Unit(Unit::Tebibyte)); }
-                    102 => { /* nothing */ }
-                    47 => { return Ok(Token::Unit(Unit::Terabyte)); }
-                    103 => { /* nothing */ }
-                    48 => { return Ok(Token::Unit(Unit::Exbibyte)); }
-                    104 => { /* nothing */ }
-                    49 => { return Ok(Token::Unit(Unit::Kibibyte)); }
-                    105 => { /* nothing */ }
-                    50 => { return Ok(Token::Unit(Unit::Kilobyte)); }
-                    106 => { /* nothing */ }
-                    51 => { return Ok(Token::Unit(Unit::Mebibyte)); }
-                    107 => { /* nothing */ }
-                    52 => { return Ok(Token::Unit(Unit::Megabyte)); }
-                    108 => { /* nothing */ }
-                    53 => { return Ok(Token::Unit(Unit::Gibibyte)); }
-                    109 => { /* nothing */ }
-                    54 => { return Ok(Token::Unit(Unit::Gigabyte)); }
-                    110 => { /* nothing */ }
-                    55 => { return Ok(Token::Unit(Unit::Petabyte)); }
-                    111 => { /* nothing */ }
-                    56 => { return Ok(Token::Unit(Unit::Pebibyte)); }
-                    112 => { /* nothing */ }
-
-                    _ => {
-                        return Err(Error::Unmatch);
-                    }
-                }
+        let ((decimal, decimal_name), (binary, binary_name)) = prefix_units(letter)?;
+        for &(unit, unit_name) in &[(decimal, decimal_name), (binary, binary_name)] {
+            if tail.starts_with(unit_name) {
+                return Some((name(unit, false), plural(1 + unit_name.len())));
             }
-        }   // loop
-        // never reach end of function
-    }
-
-    pub fn read_number(&mut self) -> Result<f64, ScanError> {
-        let token = self.yylex().map_err(|err| ScanError::TokenError(err))?;
-        match token {
-            Token::Unit(_) => Err(ScanError::TokenError(Error::Unmatch)),
-            Token::Number => {
-                let text = self.yytext();
-                let num = text.parse::<f64>();
-                num.map_err(|err| ScanError::ParseError(err))
+            // The same name with `bit` in place of `byte`, e.g. `kilobit`
+            let stem = &unit_name[..unit_name.len() - "byte".len()];
+            if tail.starts_with(stem) && tail[stem.len()..].starts_with(b"bit") {
+                return Some((name(unit, true), plural(1 + stem.len() + "bit".len())));
             }
         }
+
+        let (unit, len) = match tail.first() {
+            Some(b'i') => (binary, 2),
+            _ => (decimal, 1),
+        };
+        let (suffix, len) = match &rest[len..] {
+            [b'b', b'i', b't', ..] => (Suffix::Bit, plural(len + 3)),
+            [b'B', ..] => (Suffix::UpperB, len + 1),
+            [b'b', ..] => (Suffix::LowerB, len + 1),
+            _ => (Suffix::None, len),
+        };
+        let spelling = Spelling {
+            unit,
+            prefix: Some(first),
+            suffix,
+        };
+        Some((spelling, len))
+    }
+
+    /// Matches a whole name or symbol from the localized unit names at `start`, which is
+    /// read as if it were spelled out in English.
+    fn localized_unit(&self, start: usize) -> Option<(Spelling, usize)> {
+        let (unit, len) = self.unit_names?.read(&self.input[start..])?;
+        let spelling = Spelling {
+            unit,
+            prefix: None,
+            suffix: Suffix::Name { bits: false },
+        };
+        match len == self.found_len(start) {
+            true => Some((spelling, len)),
+            false => None,
+        }
+    }
+
+    /// Returns the length of the text to blame at `start`: a whole word or number, or else
+    /// a single character.
+    fn found_len(&self, start: usize) -> usize {
+        let rest = &self.input[start..];
+        match rest.first() {
+            None => 0,
+            Some(c) if c.is_ascii_alphabetic() => word_len(rest),
+            Some(_) if self.starts_number(start) => self.number_len(start),
+            Some(_) if decode(rest).is_some_and(|(c, _)| c.is_alphabetic()) => word_len(rest),
+            // Take the continuation bytes of a multi-byte UTF-8 character along
+            Some(_) => 1 + count(&rest[1..], |c| c & 0xC0 == 0x80),
+        }
+    }
+
+    fn unexpected(&self, expected: Expected, start: usize) -> ParseError {
+        let span = start..start + self.found_len(start);
+        let suggestion = match expected {
+            Expected::Unit => suggest_unit(&self.input[span.clone()]),
+            _ => None,
+        };
+        self.error(ParseErrorKind::Unexpected(expected), span, suggestion)
+    }
+
+    fn error(
+        &self,
+        kind: ParseErrorKind,
+        span: Range<usize>,
+        suggestion: Option<&'static str>,
+    ) -> ParseError {
+        let found = String::from_utf8_lossy(&self.input[span.clone()]).into_owned();
+        let column = String::from_utf8_lossy(&self.input[..span.start])
+            .chars()
+            .count();
+        ParseError {
+            kind,
+            span,
+            found,
+            suggestion,
+            input: String::from_utf8_lossy(self.input).into_owned(),
+            column,
+        }
+    }
+}
+
+/// Returns whether `c` may group the digits of a number with lenient input: a comma, an
+/// underscore, an apostrophe, or a no-break or thin space.
+fn is_separator(c: char) -> bool {
+    matches!(c, ',' | '_' | '\'' | '\u{a0}' | '\u{2009}' | '\u{202f}')
+}
+
+/// Returns the length of the word at the start of `input`, in any alphabet.
+fn word_len(input: &[u8]) -> usize {
+    let mut len = 0;
+    while let Some((c, width)) = decode(&input[len..]) {
+        if !c.is_alphabetic() {
+            break;
+        }
+        len += width;
+    }
+    len
+}
+
+/// Decodes the UTF-8 character at the start of `input` along with its length, if it is
+/// valid.
+fn decode(input: &[u8]) -> Option<(char, usize)> {
+    let len = match input.first()? {
+        0x00..=0x7F => 1,
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        _ => 4,
+    };
+    let c = str::from_utf8(input.get(..len)?).ok()?.chars().next()?;
+    Some((c, len))
+}
+
+/// A unit along with its full name, minus the first letter.
+type UnitName = (Unit, &'static [u8]);
+
+/// Returns the base-ten and base-two units starting with the lowercase `letter`.
+fn prefix_units(letter: u8) -> Option<(UnitName, UnitName)> {
+    let units = match letter {
+        b'k' => ((Unit::Kilobyte, "ilobyte"), (Unit::Kibibyte, "ibibyte")),
+        b'm' => ((Unit::Megabyte, "egabyte"), (Unit::Mebibyte, "ebibyte")),
+        b'g' => ((Unit::Gigabyte, "igabyte"), (Unit::Gibibyte, "ibibyte")),
+        b't' => ((Unit::Terabyte, "erabyte"), (Unit::Tebibyte, "ebibyte")),
+        b'p' => ((Unit::Petabyte, "etabyte"), (Unit::Pebibyte, "ebibyte")),
+        b'e' => ((Unit::Exabyte, "xabyte"), (Unit::Exbibyte, "xbibyte")),
+        b'z' => ((Unit::Zettabyte, "ettabyte"), (Unit::Zebibyte, "ebibyte")),
+        b'y' => ((Unit::Yottabyte, "ottabyte"), (Unit::Yobibyte, "obibyte")),
+        b'r' => ((Unit::Ronnabyte, "onnabyte"), (Unit::Robibyte, "obibyte")),
+        b'q' => (
+            (Unit::Quettabyte, "uettabyte"),
+            (Unit::Quebibyte, "uebibyte"),
+        ),
+        _ => return None,
+    };
+    let ((decimal, decimal_name), (binary, binary_name)) = units;
+    Some((
+        (decimal, decimal_name.as_bytes()),
+        (binary, binary_name.as_bytes()),
+    ))
+}
+
+/// Returns the SI or IEC symbol for `unit`, or for the same number of bits if `bits` is set.
+fn si_symbol(unit: Unit, bits: bool) -> &'static str {
+    let (bytes_symbol, bits_symbol) = match unit {
+        Unit::Byte => ("B", "b"),
+        Unit::Kilobyte => ("kB", "kb"),
+        Unit::Kibibyte => ("KiB", "Kib"),
+        Unit::Megabyte => ("MB", "Mb"),
+        Unit::Mebibyte => ("MiB", "Mib"),
+        Unit::Gigabyte => ("GB", "Gb"),
+        Unit::Gibibyte => ("GiB", "Gib"),
+        Unit::Terabyte => ("TB", "Tb"),
+        Unit::Tebibyte => ("TiB", "Tib"),
+        Unit::Petabyte => ("PB", "Pb"),
+        Unit::Pebibyte => ("PiB", "Pib"),
+        Unit::Exabyte => ("EB", "Eb"),
+        Unit::Exbibyte => ("EiB", "Eib"),
+        Unit::Zettabyte => ("ZB", "Zb"),
+        Unit::Zebibyte => ("ZiB", "Zib"),
+        Unit::Yottabyte => ("YB", "Yb"),
+        Unit::Yobibyte => ("YiB", "Yib"),
+        Unit::Ronnabyte => ("RB", "Rb"),
+        Unit::Robibyte => ("RiB", "Rib"),
+        Unit::Quettabyte => ("QB", "Qb"),
+        Unit::Quebibyte => ("QiB", "Qib"),
+    };
+    match bits {
+        true => bits_symbol,
+        false => bytes_symbol,
+    }
+}
+
+/// Spellings that aren't accepted but are close enough to a unit to suggest it, along with
+/// the abbreviation to suggest. Valid spellings are matched case-insensitively, so that
+/// e.g. `kB` suggests `KB`.
+const SUGGESTIONS: [(&str, &str); 69] = [
+    ("b", "B"),
+    ("byte", "B"),
+    ("kb", "KB"),
+    ("kilobyte", "KB"),
+    ("kilo", "KB"),
+    ("mb", "MB"),
+    ("megabyte", "MB"),
+    ("meg", "MB"),
+    ("mega", "MB"),
+    ("gb", "GB"),
+    ("gigabyte", "GB"),
+    ("gig", "GB"),
+    ("giga", "GB"),
+    ("tb", "TB"),
+    ("terabyte", "TB"),
+    ("tera", "TB"),
+    ("pb", "PB"),
+    ("petabyte", "PB"),
+    ("peta", "PB"),
+    ("eb", "EB"),
+    ("exabyte", "EB"),
+    ("exa", "EB"),
+    ("zb", "ZB"),
+    ("zettabyte", "ZB"),
+    ("zetta", "ZB"),
+    ("yb", "YB"),
+    ("yottabyte", "YB"),
+    ("yotta", "YB"),
+    ("rb", "RB"),
+    ("ronnabyte", "RB"),
+    ("ronna", "RB"),
+    ("qb", "QB"),
+    ("quettabyte", "QB"),
+    ("quetta", "QB"),
+    ("kib", "KiB"),
+    ("kibibyte", "KiB"),
+    ("kibi", "KiB"),
+    ("mib", "MiB"),
+    ("mebibyte", "MiB"),
+    ("mebi", "MiB"),
+    ("gib", "GiB"),
+    ("gibibyte", "GiB"),
+    ("gibi", "GiB"),
+    ("tib", "TiB"),
+    ("tebibyte", "TiB"),
+    ("tebi", "TiB"),
+    ("pib", "PiB"),
+    ("pebibyte", "PiB"),
+    ("pebi", "PiB"),
+    ("eib", "EiB"),
+    ("exbibyte", "EiB"),
+    ("exbi", "EiB"),
+    ("zib", "ZiB"),
+    ("zebibyte", "ZiB"),
+    ("zebi", "ZiB"),
+    ("yib", "YiB"),
+    ("yobibyte", "YiB"),
+    ("yobi", "YiB"),
+    ("rib", "RiB"),
+    ("robibyte", "RiB"),
+    ("robi", "RiB"),
+    ("qib", "QiB"),
+    ("quebibyte", "QiB"),
+    ("quebi", "QiB"),
+    ("kbyte", "KB"),
+    ("mbyte", "MB"),
+    ("gbyte", "GB"),
+    ("tbyte", "TB"),
+    ("pbyte", "PB"),
+];
+
+/// Returns the unit that `word` is most likely a misspelling of. Candidates are ranked by
+/// their edit distance to `word` (ignoring case and a plural `s`), then by how close their
+/// length is.
+fn suggest_unit(word: &[u8]) -> Option<&'static str> {
+    if word.is_empty() || word.len() > 16 || !word.iter().all(u8::is_ascii_alphabetic) {
+        return None;
     }
+    let word = word.to_ascii_lowercase();
+    let stem = match word.split_last() {
+        Some((b's', stem)) if !stem.is_empty() => stem,
+        _ => &word[..],
+    };
 
-    pub fn read_unit(&mut self) -> Result<Unit, ScanError> {
-        let token = self.yylex().map_err(|err| ScanError::TokenError(err))?;
-        match token {
-            Token::Unit(u) => Ok(u),
-            Token::Number => Err(ScanError::TokenError(Error::Unmatch))
+    let mut best = None;
+    for &(spelling, unit) in SUGGESTIONS.iter() {
+        let spelling = spelling.as_bytes();
+        let distance = edit_distance(&word, spelling).min(edit_distance(stem, spelling));
+        // Allow a typo in short names and two in longer ones, but never a total rewrite
+        let allowed = match spelling.len() {
+            0..=3 => 1,
+            _ => 2,
+        };
+        if distance > allowed || distance >= spelling.len().max(word.len()) {
+            continue;
+        }
+        let rank = (distance, spelling.len().abs_diff(word.len()));
+        match best {
+            Some((best_rank, _)) if best_rank <= rank => {}
+            _ => best = Some((rank, unit)),
         }
     }
+    best.map(|(_, unit)| unit)
+}
 
-    pub fn read_eof(&mut self) -> Result<(), ScanError> {
-        let token = self.yylex().err();
-        match token {
-            Some(Error::EOF) => Ok(()),
-            _ => Err(ScanError::TokenError(Error::Unmatch)),
+/// Returns the number of insertions, deletions, substitutions and transpositions of
+/// adjacent characters that turn `a` into `b`.
+fn edit_distance(a: &[u8], b: &[u8]) -> usize {
+    // Only the last three rows of the table are needed at any time
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = (a[i - 1] != b[j - 1]) as usize;
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
         }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
     }
+    previous[b.len()]
+}
 
+/// Counts the leading bytes of `input` matching `predicate`.
+fn count(input: &[u8], predicate: impl Fn(u8) -> bool) -> usize {
+    input.iter().take_while(|&&c| predicate(c)).count()
 }
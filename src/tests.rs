@@ -1,28 +1,136 @@
-use crate::Size;
+// The operators are deliberately tested on borrowed operands, too
+#![allow(clippy::op_ref)]
+
+use crate::*;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::convert::TryFrom;
 use std::str::FromStr;
 
 #[test]
 fn unit_tests() {
-    assert_eq!("200 bytes", format!("{}", Size::Bytes(200)));
-    assert_eq!("200 KiB", format!("{}", Size::Kibibytes(200)));
-    assert_eq!("2.00 MiB", format!("{}", Size::Kibibytes(2048)));
+    assert_eq!("200 bytes", format!("{}", Size::from_bytes(200)));
+    assert_eq!("200 KiB", format!("{}", Size::from_kib(200)));
+    assert_eq!("2.00 MiB", format!("{}", Size::from_kib(2048)));
+}
+
+#[test]
+fn display_format_specifiers() {
+    let size = Size::from_kib(2048);
+    assert_eq!("2.00 MiB  ", format!("{:10}", size));
+    assert_eq!("  2.00 MiB", format!("{:>10}", size));
+    assert_eq!("*2.00 MiB*", format!("{:*^10}", size));
+    assert_eq!("2.0 MiB", format!("{:.1}", size));
+    assert_eq!("   2.0 MiB", format!("{:>10.1}", size));
+    assert_eq!("2.10 MB", format!("{:#}", size));
+    assert_eq!("2.1 MB---", format!("{:-<#9.1}", size));
+
+    // Precision doesn't apply to whole bytes, and doesn't truncate like it does for strings
+    assert_eq!("200 bytes", format!("{:.2}", Size::from_bytes(200)));
+    assert_eq!("200 KiB", format!("{:.0}", Size::from_kib(200)));
+    assert_eq!("9 EiB", format!("{:.0}", Size::from_eib(9)));
+}
+
+#[test]
+fn size_formatter() {
+    use crate::{Base, SizeFormatter, Style};
+    use std::fmt::Write;
+
+    let formatter = SizeFormatter::new()
+        .with_base(Base::Base10)
+        .with_style(Style::AbbreviatedLowerCase)
+        .with_separator("");
+    assert_eq!("42.0kb", formatter.display(Size::from_kb(42)).to_string());
+    assert_eq!("200b", formatter.display(Size::from_bytes(200)).to_string());
+
+    // The same formatter can be reused, and writes straight into the output
+    let formatter = formatter
+        .with_precision(Precision::Decimals(1))
+        .with_separator("\u{a0}");
+    let mut out = String::new();
+    write!(out, "{}|", formatter.display(Size::from_mb(2))).unwrap();
+    write!(out, "{:>9}|", formatter.display(Size::from_mb(2))).unwrap();
+    write!(out, "{:.3}", formatter.display(Size::from_mb(2))).unwrap();
+    assert_eq!("2.0\u{a0}mb|   2.0\u{a0}mb|2.000\u{a0}mb", out);
+
+    // A formatter is `Copy`, so it can be used as a template for others
+    let full = formatter.with_style(Style::Full);
+    assert_eq!(
+        "2.0\u{a0}mb",
+        formatter.display(Size::from_mb(2)).to_string()
+    );
+    assert_eq!(
+        "2.0\u{a0}Megabytes",
+        full.display(Size::from_mb(2)).to_string()
+    );
+
+    assert_eq!(
+        Size::from_kb(42).to_string_with(Base::Base10, Style::Full),
+        SizeFormatter::new()
+            .with_base(Base::Base10)
+            .with_style(Style::Full)
+            .display(Size::from_kb(42))
+            .to_string()
+    );
+}
+
+#[test]
+fn custom_format_rules() {
+    use crate::{FormatRule, FormatRules, SizeFormatter, Unit, KILOBYTE, MEGABYTE};
+
+    // The presets are what `Base` selects
+    let formatter = SizeFormatter::new().with_rules(FormatRules::BASE10);
+    assert_eq!(
+        "2.10 MB",
+        formatter.display(Size::from_kib(2048)).to_string()
+    );
+    assert_eq!("12.0 EiB", format!("{}", Size::from_eib(12)));
+
+    // Always one decimal, and never anything bigger than megabytes
+    let rules = [
+        FormatRule::new(KILOBYTE, Unit::Byte, 0),
+        FormatRule::new(MEGABYTE, Unit::Kilobyte, 1),
+        FormatRule::new(u128::MAX, Unit::Megabyte, 1),
+    ];
+    let formatter = SizeFormatter::new().with_rules(FormatRules::new(&rules));
+    assert_eq!(
+        "999 bytes",
+        formatter.display(Size::from_bytes(999)).to_string()
+    );
+    assert_eq!("2.0 KB", formatter.display(Size::from_kb(2)).to_string());
+    assert_eq!(
+        "250.0 KB",
+        formatter.display(Size::from_kb(250)).to_string()
+    );
+    assert_eq!("3000.0 MB", formatter.display(Size::from_gb(3)).to_string());
+}
+
+#[test]
+#[should_panic(expected = "strictly ascending")]
+fn format_rules_unsorted() {
+    use crate::{FormatRule, FormatRules, Unit};
+
+    FormatRules::new(&[
+        FormatRule::new(1000, Unit::Byte, 0),
+        FormatRule::new(1000, Unit::Kilobyte, 0),
+    ]);
 }
 
 #[test]
 fn size_equality() {
     assert_eq!(
-        Size::Bytes(200),
-        Size::Bytes(200),
+        Size::from_bytes(200),
+        Size::from_bytes(200),
         "Testing equality of two identically-constructed sizes"
     );
     assert_eq!(
-        Size::Mebibytes(2),
-        Size::Kibibytes(2048),
+        Size::from_mib(2),
+        Size::from_kib(2048),
         "Testing equality of two identical sizes expressed in different units"
     );
     assert_eq!(
-        Size::Mebibytes(2u8),
-        Size::Mebibytes(2f64),
+        TypedSize::Mebibytes(2u8),
+        TypedSize::Mebibytes(2f64),
         "Testing equality of two identical sizes expressed in different types"
     );
 }
@@ -30,74 +138,1878 @@ fn size_equality() {
 #[test]
 fn size_addition() {
     // as a reference...
-    let size = &Size::Mebibytes(20) + &Size::Mebibytes(22);
-    assert_eq!(size, Size::Mebibytes(42));
+    let size = &Size::from_mib(20) + &Size::from_mib(22);
+    assert_eq!(size, Size::from_mib(42));
 
     // and not as a reference
-    let size = Size::Mebibytes(20) + Size::Mebibytes(22);
-    assert_eq!(size, Size::Mebibytes(42));
+    let size = Size::from_mib(20) + Size::from_mib(22);
+    assert_eq!(size, Size::from_mib(42));
 }
 
 #[test]
 fn primitive_multiplication() {
-    let size = &Size::Gigabytes(12) * 7;
+    let size = &Size::from_gb(12) * 7;
     assert_eq!(size.bytes(), 84000000000);
-    let size = Size::Gigabytes(12) * 7;
+    let size = Size::from_gb(12) * 7;
     assert_eq!(size.bytes(), 84000000000);
 
     // and the other way around
-    let size = 7 * Size::Gigabytes(12);
+    let size = 7 * Size::from_gb(12);
     assert_eq!(size.bytes(), 84000000000);
 
     // and with other types
-    let size = &Size::Gigabytes(12) * 7.0;
+    let size = &Size::from_gb(12) * 7.0;
     assert_eq!(size.bytes(), 84000000000);
-    let size = 7.0 * Size::Gigabytes(12);
+    let size = 7.0 * Size::from_gb(12);
     assert_eq!(size.bytes(), 84000000000);
 }
 
 #[test]
 fn primitive_division() {
-    let size = &Size::Gigabytes(12) / 13f64;
+    let size = &Size::from_gb(12) / 13f64;
     assert_eq!(size.bytes(), 923076923);
 
-    let size = Size::Gigabytes(12.0) / 13;
+    let size = Size::try_from(TypedSize::Gigabytes(12.0)).unwrap() / 13;
     assert_eq!(size.bytes(), 923076923);
 }
 
-macro_rules! size_bytes {
-    ($name:ident, $num:expr, $unit:ident, $T:ty, $res:tt) => {
-        #[test]
-        fn $name() {
-            assert_eq!(Size::$unit::<$T>($num).bytes(), $res);
+#[test]
+fn unit_metadata() {
+    assert_eq!(Unit::ALL.len(), 21);
+    assert!(Unit::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+    for unit in Unit::ALL.iter() {
+        assert_eq!(unit.counterpart().counterpart(), *unit);
+        assert_eq!(unit.counterpart().exponent(), unit.exponent());
+        assert_eq!(Unit::from_str(unit.symbol()), Ok(*unit));
+        assert_eq!(Unit::from_str(unit.name()), Ok(*unit));
+        assert_eq!(Unit::from_str(&unit.to_string()), Ok(*unit));
+        let size = Size::from_unit(3, *unit);
+        assert_eq!(size.bytes(), 3 * unit.bytes_per_unit());
+    }
+
+    assert_eq!(Unit::Mebibyte.bytes_per_unit(), MEBIBYTE);
+    assert_eq!(Unit::Mebibyte.base(), Some(Base::Base2));
+    assert_eq!(Unit::Megabyte.base(), Some(Base::Base10));
+    assert_eq!(Unit::Byte.base(), None);
+    assert_eq!(Unit::Gigabyte.exponent(), 3);
+    assert_eq!(Unit::Kibibyte.counterpart(), Unit::Kilobyte);
+    assert_eq!(Unit::Kibibyte.symbol(), "KiB");
+    assert_eq!(Unit::Kibibyte.name(), "kibibyte");
+    assert_eq!(format!("{:>5}", Unit::Kibibyte), "  KiB");
+    assert_eq!(Unit::from_str(" kb "), Ok(Unit::Kilobyte));
+    assert_eq!(Unit::from_str("Megabytes"), Ok(Unit::Megabyte));
+
+    let err = Unit::from_str("Mbi").unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::Unexpected(Expected::Unit));
+    assert_eq!(err.suggestion(), Some("MiB"));
+    let err = Unit::from_str("").unwrap_err();
+    assert_eq!(err.to_string(), "expected a unit, found end of input");
+    let err = Unit::from_str("KiB KiB").unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::Unexpected(Expected::End));
+}
+
+#[test]
+fn unit_conversion() {
+    let size = Size::from_mib(3);
+    assert_eq!(size.in_unit(Unit::Kibibyte), 3072.0);
+    assert_eq!(size.in_unit(Unit::Gibibyte), 3.0 / 1024.0);
+    assert_eq!(size.in_unit(Unit::Byte), 3145728.0);
+    assert_eq!(
+        size.in_whole_units(Unit::Megabyte),
+        (3, Size::from_bytes(145728))
+    );
+    assert_eq!(
+        Size::from_bytes(u64::MAX.into()).in_unit(Unit::Exbibyte),
+        16.0
+    );
+    assert_eq!(Size::MAX.in_unit(Unit::Quebibyte), 268435456.0);
+    assert_eq!(
+        Size::MAX.in_whole_units(Unit::Quebibyte),
+        ((1 << 28) - 1, Size::from_bytes(QUEBIBYTE - 1))
+    );
+
+    assert!(
+        matches!(Size::from_kib(2048).normalize(Base::Base2), TypedSize::Mebibytes(v) if v == 2.0)
+    );
+    assert!(
+        matches!(Size::from_bytes(1023).normalize(Base::Base2), TypedSize::Bytes(v) if v == 1023.0)
+    );
+    assert!(
+        matches!(Size::from_bytes(1500).normalize(Base::Base10), TypedSize::Kilobytes(v) if v == 1.5)
+    );
+    assert!(
+        matches!(Size::from_bytes(500).normalize(Base::Base10), TypedSize::Bytes(v) if v == 500.0)
+    );
+    // 1023.9 KiB is displayed as 1.00 MiB, so it is normalized to mebibytes as well
+    let size = Size::from_bytes(1023 * 1024 + 922);
+    assert_eq!(
+        size.to_string_with(Base::Base2, Style::Abbreviated),
+        "1.00 MiB"
+    );
+    assert!(matches!(size.normalize(Base::Base2), TypedSize::Mebibytes(v) if v < 1.0));
+}
+
+#[test]
+fn jedec_units() {
+    let size = Size::from_bytes(1536);
+    assert_eq!(size.to_string_with(Base::Jedec, Style::Smart), "1.50 KB");
+    assert_eq!(
+        size.to_string_with(Base::Jedec, Style::Full),
+        "1.50 Kilobytes"
+    );
+    assert_eq!(
+        Size::from_gib(16).to_string_with(Base::Jedec, Style::AbbreviatedLowerCase),
+        "16.0 gb"
+    );
+    assert_eq!(
+        Size::from_kb(1000).to_string_with(Base::Jedec, Style::Smart),
+        "977 KB"
+    );
+    assert_eq!(
+        Size::from_bytes(1).to_string_with(Base::Jedec, Style::Full),
+        "1 Byte"
+    );
+    assert!(
+        matches!(Size::from_mib(3).normalize(Base::Jedec), TypedSize::Mebibytes(v) if v == 3.0)
+    );
+
+    let rules = [
+        FormatRule::new(KIBIBYTE, Unit::Byte, 0),
+        FormatRule::new(u128::MAX, Unit::Kibibyte, 0),
+    ];
+    let formatter = SizeFormatter::new().with_rules(FormatRules::new(&rules).with_jedec_labels());
+    assert_eq!(formatter.display(Size::from_mib(2)).to_string(), "2048 KB");
+
+    let parser = SizeParser::new().with_jedec_units(true);
+    assert_eq!(parser.parse("1.5 MB").unwrap(), Size::from_kib(1536));
+    assert_eq!(parser.parse("2 kilobytes").unwrap(), Size::from_kib(2));
+    assert_eq!(parser.parse("2 KiB").unwrap(), Size::from_kib(2));
+    assert_eq!(parser.parse("512 B").unwrap(), Size::from_bytes(512));
+    assert!(matches!(
+        parser.parse_typed::<u32>("2 KB"),
+        Ok(TypedSize::Kibibytes(2))
+    ));
+    assert_eq!(SizeParser::new().parse("2 KB").unwrap(), Size::from_kb(2));
+}
+
+#[test]
+fn lenient_input() {
+    let parser = SizeParser::new().with_lenient_input(true);
+    assert_eq!(parser.parse("1,024 KB").unwrap(), Size::from_kb(1024));
+    assert_eq!(parser.parse("4\u{a0}GiB").unwrap(), Size::from_gib(4));
+    assert_eq!(parser.parse("1_000_000 B").unwrap(), Size::from_mb(1));
+    assert_eq!(parser.parse("2.5\tMB").unwrap(), Size::from_kb(2500));
+    assert_eq!(
+        parser
+            .parse("\u{2003}1\u{202f}234\u{202f}567.5 B\n")
+            .unwrap(),
+        Size::from_bytes(1_234_568)
+    );
+    assert_eq!(parser.parse("1'000'000").unwrap(), Size::from_mb(1));
+    assert_eq!(
+        parser.parse("12,345.5e3 B").unwrap(),
+        Size::from_bytes(12_345_500)
+    );
+    assert_eq!(
+        parser.parse_delta("-1,000 KB").unwrap(),
+        SizeDelta::from_bytes(-1_000_000)
+    );
+    assert!(matches!(
+        parser.parse_typed::<u16>("1,024 KiB"),
+        Ok(TypedSize::Kibibytes(1024))
+    ));
+
+    for input in &[
+        "1,5 MB",
+        "1,0000 B",
+        "1234,567 B",
+        "1,000_000 B",
+        "1\u{a0}000\u{2009}000 B",
+        "0.000_001 KB",
+        "1.2.3 B",
+    ] {
+        let err = parser.parse(input).unwrap_err();
+        assert_eq!(err.kind(), &ParseErrorKind::InvalidNumber, "{}", input);
+        assert_eq!(err.span(), 0..input.find(' ').unwrap());
+    }
+    // A separator that isn't between two digits doesn't belong to the number
+    assert_eq!(parser.parse("1, KB").unwrap_err().found(), ",");
+    assert_eq!(parser.parse("1 ,000 KB").unwrap_err().found(), ",");
+
+    // Without it, only ASCII spaces separate tokens and digits can't be grouped
+    assert_eq!(Size::from_str("1,024 KB").unwrap_err().found(), ",");
+    assert_eq!(Size::from_str("2.5\tMB").unwrap_err().found(), "\t");
+}
+
+#[test]
+fn locales() {
+    let size = Size::from_bytes(1_234_567_890);
+    let format = |locale| {
+        SizeFormatter::new()
+            .with_locale(locale)
+            .with_base(Base::Base10)
+            .display(size)
+            .to_string()
+    };
+    assert_eq!(format(Locale::new()), "1.23 GB");
+    assert_eq!(format(Locale::DE), "1,23 GB");
+    assert_eq!(format(Locale::FR), "1,23\u{a0}GB");
+
+    let kilobytes = [FormatRule::new(u128::MAX, Unit::Kilobyte, 3)];
+    let formatter = SizeFormatter::new()
+        .with_locale(Locale::EN)
+        .with_rules(FormatRules::new(&kilobytes));
+    assert_eq!(formatter.display(size).to_string(), "1,234,567.890 KB");
+    assert_eq!(
+        formatter.display(Size::from_bytes(999_500)).to_string(),
+        "999.500 KB"
+    );
+    assert_eq!(
+        formatter.display(Size::from_bytes(1_000_000)).to_string(),
+        "1,000.000 KB"
+    );
+    let formatter = formatter
+        .with_locale(Locale::DE.with_grouping(' ', 4))
+        .with_separator("");
+    assert_eq!(formatter.display(size).to_string(), "123 4567,890KB");
+    let formatter = formatter
+        .with_locale(Locale::DE)
+        .with_scientific_notation(3)
+        .with_precision(Precision::Decimals(1));
+    assert_eq!(formatter.display(size).to_string(), "1,2e6 KB");
+    assert_eq!(
+        SizeFormatter::new()
+            .with_locale(Locale::EN)
+            .display(Size::from_bytes(1023))
+            .to_string(),
+        "1,023 bytes"
+    );
+    assert_eq!(
+        format!(
+            "{:>12}",
+            SizeFormatter::new()
+                .with_locale(Locale::DE)
+                .display_delta(SizeDelta::from_bytes(-1023))
+        ),
+        "-1.023 bytes"
+    );
+
+    let parser = SizeParser::new().with_locale(Locale::DE);
+    assert_eq!(parser.parse("1,5 KiB").unwrap(), Size::from_bytes(1536));
+    assert_eq!(parser.parse(",5 KiB").unwrap(), Size::from_bytes(512));
+    assert_eq!(
+        parser.parse("1.234.567 B").unwrap(),
+        Size::from_bytes(1_234_567)
+    );
+    assert_eq!(
+        parser.parse("1.234,5e3 B").unwrap(),
+        Size::from_bytes(1_234_500)
+    );
+    assert_eq!(
+        parser.parse_delta("-1.023 bytes").unwrap(),
+        SizeDelta::from_bytes(-1023)
+    );
+    for input in &["1.5 KiB", "1,2,3 KiB", "12.34 B"] {
+        assert_eq!(
+            parser.parse(input).unwrap_err().kind(),
+            &ParseErrorKind::InvalidNumber,
+            "{}",
+            input
+        );
+    }
+    assert_eq!(parser.parse("1,5,").unwrap_err().span(), 0..4);
+    // Lenient input never groups digits with the decimal separator
+    let parser = parser.with_lenient_input(true);
+    assert_eq!(
+        parser.parse("1_024,5 KiB").unwrap(),
+        Size::from_bytes(1_049_088)
+    );
+
+    // Whatever a locale prints, it reads back
+    let kibibytes = [FormatRule::new(u128::MAX, Unit::Kibibyte, 2)];
+    for &locale in &[
+        Locale::EN,
+        Locale::DE,
+        Locale::FR,
+        Locale::FR.with_unit_separator("\u{2009}"),
+    ] {
+        let formatter = SizeFormatter::new()
+            .with_locale(locale)
+            .with_rules(FormatRules::new(&kibibytes));
+        let size = Size::from_bytes(123_456_789 * 1024 + 512);
+        let text = formatter.display(size).to_string();
+        assert_eq!(
+            SizeParser::new().with_locale(locale).parse(&text),
+            Ok(size),
+            "{}",
+            text
+        );
+    }
+
+    // The unit separator only takes the place of spaces between the number and the unit
+    let parser = SizeParser::new().with_locale(Locale::FR);
+    assert_eq!(parser.parse("2\u{a0}MiB"), Ok(Size::from_mib(2)));
+    assert_eq!(parser.parse("2 \u{a0} MiB"), Ok(Size::from_mib(2)));
+    for input in &["\u{a0}2 MiB", "2 MiB\u{a0}", "\u{a0}2"] {
+        let err = parser.parse(input).unwrap_err();
+        assert!(
+            matches!(err.kind(), ParseErrorKind::Unexpected(_)),
+            "{:?}",
+            input
+        );
+    }
+    let err = parser.parse_delta("-\u{a0}2 MiB").unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::Unexpected(Expected::Number));
+}
+
+#[test]
+fn unit_names() {
+    let size = Size::from_bytes(1536);
+    let format = |names, style| {
+        SizeFormatter::new()
+            .with_unit_names(names)
+            .with_style(style)
+            .display(size)
+            .to_string()
+    };
+    assert_eq!(format(UnitNames::EN, Style::Full), "1.50 Kibibytes");
+    assert_eq!(format(UnitNames::FR, Style::Smart), "1.50 Kio");
+    assert_eq!(format(UnitNames::FR, Style::Full), "1.50 Kibioctet");
+    assert_eq!(
+        format(UnitNames::FR, Style::AbbreviatedLowerCase),
+        "1.50 kio"
+    );
+    assert_eq!(format(UnitNames::DE, Style::Full), "1.50 Kibibyte");
+    assert_eq!(format(UnitNames::DE, Style::FullLowerCase), "1.50 kibibyte");
+    assert_eq!(format(UnitNames::RU, Style::Full), "1.50 Кибибайта");
+    assert_eq!(
+        format(UnitNames::RU, Style::AbbreviatedLowerCase),
+        "1.50 киб"
+    );
+    assert_eq!(format(UnitNames::JA, Style::Full), "1.50 キビバイト");
+    let formatter = SizeFormatter::new()
+        .with_unit_names(UnitNames::FR)
+        .with_base(Base::Jedec);
+    assert_eq!(
+        formatter.display(Size::from_bytes(1)).to_string(),
+        "1 octet"
+    );
+    assert_eq!(formatter.display(Size::from_kib(1)).to_string(), "1.00 ko");
+    assert_eq!(
+        SizeFormatter::new()
+            .with_unit_names(UnitNames::DE)
+            .display(Size::from_bytes(3))
+            .to_string(),
+        "3 Bytes"
+    );
+
+    // The form of a name goes with the number as it is printed
+    let formatter = || SizeFormatter::new().with_style(Style::FullLowerCase);
+    let whole = || formatter().with_precision(Precision::Decimals(0));
+    assert_eq!(whole().display(Size::from_kib(1)).to_string(), "1 kibibyte");
+    assert_eq!(
+        formatter().display(Size::from_kib(1)).to_string(),
+        "1.00 kibibytes"
+    );
+    let french = whole().with_unit_names(UnitNames::FR);
+    assert_eq!(french.display(Size::from_bytes(0)).to_string(), "0 octet");
+    assert_eq!(french.display(Size::from_bytes(2)).to_string(), "2 octets");
+    let russian = whole().with_unit_names(UnitNames::RU);
+    let names: Vec<_> = [1, 2, 5, 11, 12, 21, 24, 111, 1000]
+        .iter()
+        .map(|&bytes| russian.display(Size::from_bytes(bytes)).to_string())
+        .collect();
+    let expected = [
+        "1 байт",
+        "2 байта",
+        "5 байт",
+        "11 байт",
+        "12 байт",
+        "21 байт",
+        "24 байта",
+        "111 байт",
+        "1000 байт",
+    ];
+    assert_eq!(names, expected);
+    assert_eq!(
+        russian.display(Size::from_kib(22)).to_string(),
+        "22 кибибайта"
+    );
+    let russian = formatter().with_unit_names(UnitNames::RU);
+    assert_eq!(
+        russian.display(Size::from_kib(1)).to_string(),
+        "1.00 кибибайта"
+    );
+    assert_eq!(UnitNames::RU.text(Unit::Yottabyte).symbol(), "ЙБ");
+
+    // The English vocabulary is what units have always been printed with
+    for &unit in Unit::ALL.iter() {
+        assert_eq!(UnitNames::EN.text(unit).symbol(), unit.symbol());
+        assert_eq!(
+            UnitNames::EN.text(unit).plural(),
+            format!("{}s", unit.name())
+        );
+    }
+
+    assert_eq!(UnitNames::for_language("fr"), Some(UnitNames::FR));
+    assert_eq!(UnitNames::for_language("FR-ca"), Some(UnitNames::FR));
+    assert_eq!(UnitNames::for_language("ja_JP"), Some(UnitNames::JA));
+    assert_eq!(UnitNames::for_language("xx"), None);
+    static SWISS: UnitNames = UnitNames::DE
+        .with_language("de-CH")
+        .with_unit(Unit::Kilobyte, UnitText::new("Kilobyte", "Kilobyte", "KB"));
+    UnitNames::register(&SWISS);
+    assert_eq!(UnitNames::for_language("de-ch"), Some(&SWISS));
+    assert_eq!(UnitNames::for_language("de-AT"), Some(UnitNames::DE));
+    assert_eq!(SWISS.text(Unit::Kilobyte).symbol(), "KB");
+    assert_eq!(SWISS.text(Unit::Megabyte).symbol(), "MB");
+    UnitNames::unregister(&SWISS);
+    assert_eq!(UnitNames::for_language("de-ch"), Some(UnitNames::DE));
+
+    let parser = SizeParser::new().with_unit_names(UnitNames::FR);
+    assert_eq!(parser.parse("2 Mo").unwrap(), Size::from_mb(2));
+    assert_eq!(parser.parse("2 mo").unwrap(), Size::from_mb(2));
+    assert_eq!(parser.parse("2 MÉGAOCTETS").unwrap(), Size::from_mb(2));
+    assert_eq!(parser.parse("2 octets").unwrap(), Size::from_bytes(2));
+    assert_eq!(parser.parse("2o").unwrap(), Size::from_bytes(2));
+    assert_eq!(parser.parse("2 MB").unwrap(), Size::from_mb(2));
+    assert_eq!(parser.parse("2 mO").unwrap_err().found(), "mO");
+    assert_eq!(parser.parse("2 Moo").unwrap_err().found(), "Moo");
+    assert!(matches!(
+        parser.parse_typed::<u8>("2 Gio"),
+        Ok(TypedSize::Gibibytes(2))
+    ));
+    assert_eq!(SizeParser::new().parse("2 Mo").unwrap_err().found(), "Mo");
+
+    let parser = SizeParser::new().with_unit_names(UnitNames::RU);
+    assert_eq!(parser.parse("512 байт").unwrap(), Size::from_bytes(512));
+    assert_eq!(parser.parse("3 МиБ").unwrap(), Size::from_mib(3));
+    assert_eq!(parser.parse("3 миб").unwrap(), Size::from_mib(3));
+    assert_eq!(parser.parse("3 Мегабайт").unwrap(), Size::from_mb(3));
+    assert_eq!(parser.parse("3 мегабайта").unwrap(), Size::from_mb(3));
+    assert_eq!(parser.parse("1 йоттабайт").unwrap(), Size::from_yb(1));
+    let err = parser.parse("3 мегабайтов").unwrap_err();
+    assert_eq!((err.found(), err.span()), ("мегабайтов", 2..22));
+    let parser = SizeParser::new().with_unit_names(UnitNames::JA);
+    assert_eq!(parser.parse("5キロバイト").unwrap(), Size::from_kb(5));
+
+    // A localized symbol is only read as such where the English rules don't apply
+    let parser = SizeParser::new().with_unit_names(UnitNames::DE);
+    assert_eq!(parser.parse("2 kB").unwrap(), Size::from_kb(2));
+    let parser = parser.with_mode(ParseMode::Strict);
+    assert_eq!(parser.parse("2 kB").unwrap(), Size::from_kb(2));
+    assert_eq!(parser.parse("2 Mb").unwrap(), Size::from_kb(250));
+    assert_eq!(
+        parser.parse("2 mb").unwrap_err().kind(),
+        &ParseErrorKind::AmbiguousUnit
+    );
+
+    // Whatever a vocabulary prints, it reads back as the English text would
+    let styles = [
+        Style::Smart,
+        Style::Full,
+        Style::FullLowerCase,
+        Style::Abbreviated,
+        Style::AbbreviatedLowerCase,
+    ];
+    for &names in &[
+        UnitNames::EN,
+        UnitNames::FR,
+        UnitNames::DE,
+        UnitNames::RU,
+        UnitNames::JA,
+    ] {
+        for &style in styles.iter() {
+            for &bytes in &[1, 1000, 1024, 3 * GIBIBYTE, QUEBIBYTE] {
+                let formatter = SizeFormatter::new()
+                    .with_style(style)
+                    .with_precision(Precision::Decimals(0));
+                let english = formatter.display(Size::from_bytes(bytes)).to_string();
+                let text = formatter
+                    .with_unit_names(names)
+                    .display(Size::from_bytes(bytes))
+                    .to_string();
+                let parsed = SizeParser::new()
+                    .with_unit_names(names)
+                    .parse_typed::<u128>(&text);
+                assert_eq!(
+                    parsed,
+                    SizeParser::new().parse_typed::<u128>(&english),
+                    "{}",
+                    text
+                );
+            }
         }
     }
 }
 
-size_bytes!(size_bytes_b_i64_1, 792633534438178879, Bytes, i64, 792633534438178879);
-size_bytes!(size_bytes_mb_u64_1, 16172825064112138, Kilobytes, u64, 16172825064112138000);
-size_bytes!(size_bytes_tib_f64_1, -1.333602886575971, Tebibytes, f64, 18446742607397670991);
+#[test]
+fn strict_units() {
+    let parser = SizeParser::new().with_mode(ParseMode::Strict);
+    assert_eq!(
+        parser.parse("100 Mb").unwrap(),
+        Size::from_bytes(12_500_000)
+    );
+    assert_eq!(
+        parser.parse("100 Mbit").unwrap(),
+        Size::from_bytes(12_500_000)
+    );
+    assert_eq!(parser.parse("3 kB").unwrap(), Size::from_kb(3));
+    assert_eq!(parser.parse("3 KiB").unwrap(), Size::from_kib(3));
+    assert_eq!(parser.parse("8 Kib").unwrap(), Size::from_kib(1));
+    assert_eq!(parser.parse("2 kilobits").unwrap(), Size::from_bytes(250));
+    assert_eq!(parser.parse("2 Gigabytes").unwrap(), Size::from_gb(2));
+    assert_eq!(parser.parse("16 bits").unwrap(), Size::from_bytes(2));
+    assert_eq!(parser.parse("42").unwrap(), Size::from_bytes(42));
+
+    // Bits that don't make whole bytes are only rounded when asked to
+    assert_eq!(
+        parser.parse("12 bits").unwrap_err().kind(),
+        &ParseErrorKind::FractionalBytes
+    );
+    assert_eq!(
+        parser.parse("1 b").unwrap_err().kind(),
+        &ParseErrorKind::FractionalBytes
+    );
+    assert_eq!(
+        parser.parse("0.5 B").unwrap_err().kind(),
+        &ParseErrorKind::FractionalBytes
+    );
+    let rounding = parser
+        .clone()
+        .with_fractional_bytes(FractionalBytes::Round(RoundingMode::HalfEven));
+    assert_eq!(rounding.parse("12 bits").unwrap(), Size::from_bytes(2));
+    assert_eq!(rounding.parse("1 b").unwrap(), Size::from_bytes(0));
+    assert!(matches!(
+        parser.parse_typed::<u8>("8 Mb"),
+        Ok(TypedSize::Megabytes(1))
+    ));
+    assert_eq!(
+        parser.parse_delta("-16 Mb").unwrap(),
+        SizeDelta::from_bytes(-2_000_000)
+    );
+
+    for &(input, suggestion) in &[
+        ("1 KB", "kB"),
+        ("1 mb", "Mb"),
+        ("1 M", "MB"),
+        ("1 kiB", "KiB"),
+        ("1 Gi", "GiB"),
+    ] {
+        let err = parser.parse(input).unwrap_err();
+        assert_eq!(err.kind(), &ParseErrorKind::AmbiguousUnit, "{}", input);
+        assert_eq!(err.span(), 2..input.len());
+        assert_eq!(err.suggestion(), Some(suggestion));
+    }
+    assert_eq!(
+        parser.parse("5 KB").unwrap_err().to_string(),
+        "ambiguous unit `KB` at offset 2 (did you mean `kB`?)"
+    );
+
+    let jedec = parser.with_jedec_units(true);
+    assert_eq!(jedec.parse("1 KB").unwrap(), Size::from_kib(1));
+    assert_eq!(jedec.parse("8 kb").unwrap(), Size::from_kib(1));
+
+    // `FromStr` stays lenient and never reads bits
+    assert_eq!(Size::from_str("1 mb").unwrap(), Size::from_mb(1));
+    assert_eq!(Size::from_str("1 KB").unwrap(), Size::from_kb(1));
+    for input in &["1 kB", "1 Mbit", "1 kilobit", "1 bits"] {
+        let err = Size::from_str(input).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &ParseErrorKind::Unexpected(Expected::Unit),
+            "{}",
+            input
+        );
+    }
+}
 
 #[test]
-fn size_from_str() {
-    let size = Size::<f64>::from_str("200");
-    assert_eq!(size.unwrap(), Size::Bytes(200));
+fn base_and_style_names() {
+    for base in [Base::Base2, Base::Base10, Base::Jedec].iter() {
+        assert_eq!(Base::from_str(&base.to_string()), Ok(*base));
+    }
+    assert_eq!(Base::from_str("Binary"), Ok(Base::Base2));
+    assert_eq!(Base::from_str(" 10"), Ok(Base::Base10));
+    assert_eq!(Base::from_str("base_10"), Ok(Base::Base10));
+    assert_eq!(Base::from_str("JEDEC"), Ok(Base::Jedec));
+
+    let styles = [
+        Style::Abbreviated,
+        Style::AbbreviatedLowerCase,
+        Style::Full,
+        Style::Smart,
+        Style::FullLowerCase,
+    ];
+    for style in styles.iter() {
+        assert_eq!(Style::from_str(&style.to_string()), Ok(*style));
+    }
+    assert_eq!(Style::FullLowerCase.to_string(), "full-lowercase");
+    assert_eq!(Style::from_str("FULL_LOWERCASE"), Ok(Style::FullLowerCase));
+
+    let err = Style::from_str(" ful").unwrap_err();
+    assert_eq!(err.span(), 1..4);
+    assert_eq!(err.suggestion(), Some("full"));
+    assert_eq!(
+        err.to_string(),
+        "expected a style, found `ful` at offset 1 (did you mean `full`?)"
+    );
+    let err = Base::from_str("hex").unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::Unexpected(Expected::Base));
+    assert_eq!(err.suggestion(), None);
+}
+
+#[test]
+fn size_ordering() {
+    let mut sizes = vec![
+        Size::from_mib(1),
+        Size::from_kib(1),
+        Size::from_kb(1),
+        Size::from_bytes(0),
+    ];
+    sizes.sort();
+    assert_eq!(
+        sizes,
+        [
+            Size::from_bytes(0),
+            Size::from_kb(1),
+            Size::from_kib(1),
+            Size::from_mib(1)
+        ]
+    );
+    assert_eq!(sizes.iter().max(), Some(&Size::from_mib(1)));
+
+    assert!(Size::ZERO < Size::from_bytes(1));
+    assert!(Size::from_eib(15) < Size::MAX);
+
+    // typed sizes compare by their number of bytes, too
+    assert!(TypedSize::Kibibytes(1) > TypedSize::Kilobytes(1.02));
+    assert!(TypedSize::Kibibytes(1) <= TypedSize::Bytes(1024.0));
+    assert!(TypedSize::Bytes(-1) < TypedSize::Bytes(0));
+    assert!(TypedSize::Quettabytes(400_000_000) > TypedSize::Bytes(u128::MAX));
+    assert!(TypedSize::Bytes(f64::INFINITY) > TypedSize::Bytes(u128::MAX));
+    assert!(TypedSize::Bytes(f64::NEG_INFINITY) < TypedSize::Bytes(0));
+    assert_eq!(
+        TypedSize::Bytes(f64::NAN).partial_cmp(&TypedSize::Bytes(0.0)),
+        None
+    );
+
+    // negative sizes, fractions of a byte and sizes past u128::MAX bytes compare exactly
+    assert!(TypedSize::Bytes(-1) > TypedSize::Kibibytes(-5));
+    assert!(TypedSize::Kibibytes(-1.5) < TypedSize::Bytes(-1535));
+    assert_eq!(TypedSize::Kibibytes(-1.5), TypedSize::Bytes(-1536));
+    assert_eq!(TypedSize::Bytes(-0.0), TypedSize::Bytes(0));
+    assert!(TypedSize::Bytes(0.5) > TypedSize::Bytes(0));
+    assert!(TypedSize::Bytes(0.5) < TypedSize::Bytes(1));
+    assert!(TypedSize::Quettabytes(400_000_000) < TypedSize::Quettabytes(800_000_000));
+    assert_eq!(
+        TypedSize::Quettabytes(400_000_000),
+        TypedSize::Yottabytes(400_000_000_000_000u64)
+    );
+    assert_eq!(TypedSize::Quebibytes(0.5), TypedSize::Robibytes(512));
+    assert_eq!(
+        TypedSize::Quebibytes(1u128 << 127),
+        TypedSize::Robibytes(2f64.powi(137))
+    );
+    assert_eq!(
+        TypedSize::Quettabytes(u128::MAX),
+        TypedSize::Quettabytes(u128::MAX)
+    );
+    assert!(TypedSize::Quettabytes(u128::MAX) > TypedSize::Ronnabytes(u128::MAX));
+    assert!(TypedSize::Quettabytes(u128::MAX - 1) < TypedSize::Quettabytes(u128::MAX));
+    assert_eq!(
+        TypedSize::Quebibytes(1u64 << 28).cmp(&TypedSize::Quebibytes(1 << 29)),
+        Ordering::Less
+    );
+    assert_eq!(TypedSize::Bytes(f64::MAX), TypedSize::Bytes(f64::MAX));
+    assert!(TypedSize::Bytes(f64::MAX) < TypedSize::Bytes(f64::INFINITY));
+    assert!(TypedSize::Bytes(f64::MIN) > TypedSize::Bytes(f64::NEG_INFINITY));
+}
+
+#[test]
+fn size_hash() {
+    let mut map = BTreeMap::new();
+    map.insert(Size::from_kib(1), "KiB");
+    map.insert(Size::from_kb(1), "KB");
+    assert_eq!(map.get(&Size::from_bytes(1024)), Some(&"KiB"));
+    assert_eq!(map.keys().next(), Some(&Size::from_bytes(1000)));
+
+    let set: HashSet<_> = vec![
+        Size::from_kib(1),
+        Size::from_bytes(1024),
+        Size::from_bytes(1000),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&Size::from_kb(1)));
+
+    let set: HashSet<_> = vec![
+        TypedSize::Kibibytes(1),
+        TypedSize::Bytes(1024),
+        TypedSize::Kilobytes(1),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&TypedSize::Bytes(1000)));
+
+    let sizes = vec![
+        TypedSize::Bytes(-1),
+        TypedSize::Kibibytes(-5),
+        TypedSize::Bytes(-5120),
+    ];
+    let set: HashSet<_> = sizes.into_iter().collect();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&TypedSize::Bytes(-1)));
+}
+
+#[test]
+fn enum_ordering() {
+    assert!(Unit::Byte < Unit::Kilobyte);
+    assert!(Unit::Kilobyte < Unit::Kibibyte);
+    assert!(Unit::Kibibyte < Unit::Megabyte);
+    assert!(Unit::Exabyte < Unit::Exbibyte);
+    assert_eq!(Unit::Gibibyte.max(Unit::Gigabyte), Unit::Gibibyte);
 
-    let size = Size::<f64>::from_str("200 bytes");
-    assert_eq!(size.unwrap(), Size::Bytes(200));
+    // styles and bases can be reused, sizes copied
+    let (base, style) = (Base::Base10, Style::Abbreviated);
+    let size = Size::from_kb(3);
+    assert_eq!(size.to_string_with(base, style), "3.00 KB");
+    assert_eq!(size.to_string_with(base, style), "3.00 KB");
+    assert_eq!(size + size, Size::from_kb(6));
+}
+
+#[test]
+fn size_division() {
+    let used = Size::from_gib(73);
+    let disk = Size::from_gib(100);
+    assert_eq!(&used / &disk, 0.73);
+    assert_eq!(used / Size::from_mib(512), 146.0);
+    assert!((Size::from_bytes(0) / Size::from_bytes(0)).is_nan());
+
+    let block = Size::from_kib(4);
+    assert_eq!(Size::from_kib(8).div_floor(block), 2);
+    assert_eq!(Size::from_kib(8).div_ceil(block), 2);
+    assert_eq!(Size::from_bytes(8193).div_floor(block), 2);
+    assert_eq!(Size::from_bytes(8193).div_ceil(block), 3);
+    assert_eq!(Size::from_bytes(0).div_ceil(block), 0);
+    assert_eq!(&Size::from_bytes(8193) % &block, Size::from_bytes(1));
+    assert_eq!(Size::from_kib(8) % Size::from_kb(1), Size::from_bytes(192));
+}
+
+#[test]
+#[should_panic]
+fn size_div_floor_by_zero() {
+    Size::from_kib(1).div_floor(Size::ZERO);
+}
+
+#[test]
+fn size_min_max() {
+    assert_eq!(Size::from_kib(1).min(Size::from_kb(1)), Size::from_kb(1));
+    assert_eq!(Size::from_kib(1).max(Size::from_kb(1)), Size::from_kib(1));
+    assert_eq!(Size::MAX.min(Size::ZERO), Size::ZERO);
+
+    let (low, high) = (Size::from_kib(4), Size::from_mib(1));
+    assert_eq!(Size::from_bytes(10).clamp(Size::from_kib(4), high), low);
+    assert_eq!(
+        Size::from_gib(1).clamp(low, Size::from_mib(1)),
+        Size::from_mib(1)
+    );
+    assert_eq!(
+        Size::from_kib(64).clamp(Size::from_kib(4), Size::from_mib(1)),
+        Size::from_kib(64)
+    );
+}
+
+#[test]
+fn size_approx_eq() {
+    let size = Size::from_gb(1);
+    assert!(size.approx_eq(Size::from_gib(1), Size::from_mb(74)));
+    assert!(!size.approx_eq(Size::from_gib(1), Size::from_mb(73)));
+    assert!(size.approx_eq(size, Size::from_bytes(0)));
+    assert!(size.approx_eq_relative(Size::from_gib(1), 0.07));
+    assert!(!size.approx_eq_relative(Size::from_gib(1), 0.06));
+    assert!(Size::from_bytes(0).approx_eq_relative(Size::from_bytes(0), 0.0));
+}
+
+#[test]
+fn checked_arithmetic() {
+    assert_eq!(
+        Size::from_kib(1).checked_add(Size::from_bytes(1)),
+        Some(Size::from_bytes(1025))
+    );
+    assert_eq!(Size::MAX.checked_add(Size::from_bytes(1)), None);
+    assert_eq!(Size::MAX.saturating_add(Size::from_bytes(1)), Size::MAX);
+    assert_eq!(
+        Size::MAX.wrapping_add(Size::from_bytes(2)),
+        Size::from_bytes(1)
+    );
+    assert_eq!(
+        Size::MAX.overflowing_add(Size::from_bytes(0)),
+        (Size::MAX, false)
+    );
+
+    // integral scalars are exact, even beyond the precision of an `f64`
+    assert_eq!(
+        Size::from_bytes(u128::MAX / 3).checked_mul(3),
+        Some(Size::MAX)
+    );
+    assert_eq!(
+        Size::from_bytes(u128::MAX / 3).checked_mul(3.0),
+        Some(Size::MAX)
+    );
+    assert_eq!(Size::MAX.checked_mul(2), None);
+    assert_eq!(Size::MAX.saturating_mul(2), Size::MAX);
+    assert_eq!(
+        Size::MAX.overflowing_mul(2),
+        (Size::from_bytes(u128::MAX - 1), true)
+    );
+    assert_eq!(
+        Size::from_kb(3).checked_mul(1.5),
+        Some(Size::from_bytes(4500))
+    );
+    assert_eq!(
+        Size::from_bytes(3).checked_mul(0.5),
+        Some(Size::from_bytes(1))
+    );
+
+    // negative results are out of range, but negative zero isn't
+    assert_eq!(Size::from_bytes(1).checked_mul(-1), None);
+    assert_eq!(Size::from_bytes(1).saturating_mul(-1), Size::from_bytes(0));
+    assert_eq!(Size::from_bytes(1).overflowing_mul(-1), (Size::MAX, true));
+    assert_eq!(Size::from_bytes(1).overflowing_mul(-1.0), (Size::MAX, true));
+    assert_eq!(
+        Size::from_bytes(0).checked_mul(-1),
+        Some(Size::from_bytes(0))
+    );
+    assert_eq!(
+        Size::from_bytes(1).checked_mul(-0.5),
+        Some(Size::from_bytes(0))
+    );
+
+    // non-finite scalars
+    assert_eq!(Size::from_bytes(1).checked_mul(f64::INFINITY), None);
+    assert_eq!(Size::from_bytes(1).saturating_mul(f64::INFINITY), Size::MAX);
+    assert_eq!(
+        Size::from_bytes(1).wrapping_mul(f64::INFINITY),
+        Size::from_bytes(0)
+    );
+    assert_eq!(Size::from_bytes(1).checked_mul(f64::NAN), None);
+    assert_eq!(
+        Size::from_bytes(1).saturating_mul(f64::NAN),
+        Size::from_bytes(0)
+    );
+    assert_eq!(
+        Size::from_bytes(1).overflowing_mul(f64::NAN),
+        (Size::from_bytes(0), true)
+    );
+
+    assert_eq!(
+        Size::from_kib(1).checked_div(3),
+        Some(Size::from_bytes(341))
+    );
+    assert_eq!(
+        Size::from_kib(1).checked_div(0.5),
+        Some(Size::from_bytes(2048))
+    );
+    assert_eq!(Size::from_kib(1).checked_div(-2), None);
+    assert_eq!(
+        Size::from_kib(1).wrapping_div(-2),
+        Size::from_bytes(512u128.wrapping_neg())
+    );
+    assert_eq!(Size::MAX.checked_div(0.25), None);
+    assert_eq!(Size::MAX.saturating_div(0.25), Size::MAX);
+    assert_eq!(
+        Size::from_kib(1).checked_div(f64::INFINITY),
+        Some(Size::from_bytes(0))
+    );
 
-    let size = Size::<f64>::from_str("200 KiB");
-    assert_eq!(size.unwrap(), Size::Kibibytes(200));
+    // division by zero is infinite, or not a number for zero bytes
+    assert_eq!(Size::from_kib(1).checked_div(0), None);
+    assert_eq!(Size::from_kib(1).saturating_div(0), Size::MAX);
+    assert_eq!(Size::from_kib(1).overflowing_div(0.0), (Size::ZERO, true));
+    assert_eq!(Size::ZERO.checked_div(0), None);
+    assert_eq!(Size::ZERO.saturating_div(0), Size::ZERO);
 
-    let size = Size::<f64>::from_str("2.50 MiB");
-    assert_eq!(size.unwrap(), Size::Kibibytes(2560));
+    // the operators are exact
+    assert_eq!(
+        Size::from_bytes((1 << 60) + 1) * 1u8,
+        Size::from_bytes((1 << 60) + 1)
+    );
+    assert_eq!(
+        Size::from_bytes((1 << 60) + 3) / 2,
+        Size::from_bytes((1 << 59) + 1)
+    );
+    assert_eq!(3 * Size::from_kib(1), Size::from_bytes(3072));
+    assert_eq!(1.5 * Size::from_kib(1), Size::from_bytes(1536));
+    assert_eq!(Size::from_kib(1) / 0.5, Size::from_kib(2));
+}
 
-    let size = Size::<f64>::from_str("2G");
-    assert_eq!(size.unwrap(), Size::Gigabytes(2));
+#[test]
+#[should_panic(expected = "attempt to multiply with overflow")]
+fn size_multiplication_negative() {
+    let _ = -2 * Size::from_kib(1);
+}
+
+#[test]
+#[should_panic(expected = "attempt to multiply with overflow")]
+fn size_multiplication_overflow() {
+    let _ = Size::MAX * 2;
+}
+
+#[test]
+#[should_panic(expected = "attempt to divide by zero")]
+fn size_division_by_zero() {
+    let _ = Size::from_kib(1) / 0;
+}
+
+#[test]
+fn size_subtraction() {
+    let delta = Size::from_mib(20) - Size::from_mib(22);
+    assert_eq!(delta, SizeDelta::from_bytes(-2 * MEBIBYTE as i128));
+    assert_eq!(&Size::from_mib(22) - &Size::from_mib(20), -delta);
+    assert_eq!(delta.abs(), Size::from_mib(2));
+    assert_eq!(
+        SizeDelta::from_bytes(i128::MIN).abs(),
+        Size::from_bytes(1 << 127)
+    );
+    assert!(delta.is_negative());
+    assert_eq!(
+        delta + SizeDelta::from_bytes(1),
+        SizeDelta::from_bytes(1 - 2 * MEBIBYTE as i128)
+    );
+    assert_eq!(
+        Size::from_bytes(0) - Size::from_bytes(1 << 127),
+        SizeDelta::from_bytes(i128::MIN)
+    );
+    assert_eq!(
+        Size::MAX - Size::from_bytes(1 << 127),
+        SizeDelta::from_bytes(i128::MAX)
+    );
+
+    assert_eq!(Size::from_mib(22) + delta, Size::from_mib(20));
+    assert_eq!(delta + Size::from_mib(22), Size::from_mib(20));
+    assert_eq!(format!("{:?}", delta), "-2097152 bytes");
+}
+
+#[test]
+#[should_panic(expected = "attempt to add with overflow")]
+fn size_delta_below_zero() {
+    let _ = Size::from_mib(1) + (Size::from_mib(1) - Size::from_mib(2) * 2);
+}
+
+#[test]
+fn size_delta_arithmetic() {
+    let (max, min) = (
+        SizeDelta::from_bytes(i128::MAX),
+        SizeDelta::from_bytes(i128::MIN),
+    );
+    let one = SizeDelta::from_bytes(1);
+    assert_eq!(one - SizeDelta::from_bytes(3), SizeDelta::from_bytes(-2));
+    assert_eq!(
+        max.checked_add(-one),
+        Some(SizeDelta::from_bytes(i128::MAX - 1))
+    );
+    assert_eq!(max.checked_add(one), None);
+    assert_eq!(min.checked_sub(one), None);
+    assert_eq!(max.saturating_add(one), max);
+    assert_eq!(min.saturating_add(-one), min);
+    assert_eq!(min.saturating_sub(one), min);
+    assert_eq!(one.saturating_sub(min), max);
+}
+
+#[test]
+#[should_panic(expected = "attempt to add with overflow")]
+fn size_delta_addition_overflow() {
+    let _ = SizeDelta::from_bytes(i128::MAX) + SizeDelta::from_bytes(1);
+}
+
+#[test]
+#[should_panic(expected = "attempt to subtract with overflow")]
+fn size_delta_subtraction_overflow() {
+    let _ = SizeDelta::from_bytes(i128::MIN) - SizeDelta::from_bytes(1);
+}
+
+#[test]
+#[should_panic(expected = "attempt to negate with overflow")]
+fn size_delta_negation_out_of_range() {
+    let _ = -(Size::ZERO - Size::from_bytes(1 << 127));
+}
+
+#[test]
+#[should_panic(expected = "attempt to subtract with overflow")]
+fn size_delta_out_of_range() {
+    let _ = Size::ZERO - Size::MAX;
+}
+
+#[test]
+fn size_delta_formatting() {
+    assert_eq!(SizeDelta::from_bytes(1_258_291).to_string(), "+1.20 MiB");
+    assert_eq!(SizeDelta::from_bytes(-512 * 1024).to_string(), "-512 KiB");
+    assert_eq!(SizeDelta::from_bytes(0).to_string(), "0 bytes");
+    assert_eq!(SizeDelta::from_bytes(-1).to_string(), "-1 byte");
+    assert_eq!(format!("{:#}", SizeDelta::from_bytes(-1500)), "-1.50 KB");
+    assert_eq!(
+        format!("{:>10.1}", SizeDelta::from_bytes(1536)),
+        "  +1.5 KiB"
+    );
+    assert_eq!(
+        format!("{}", SizeDelta::from_bytes(i128::MIN)),
+        "-134217728 QiB"
+    );
+
+    // floor and ceil round the signed value, not its magnitude
+    let floor = SizeFormatter::new().with_rounding(RoundingMode::Floor);
+    assert_eq!(
+        floor.display_delta(SizeDelta::from_bytes(1535)).to_string(),
+        "+1.49 KiB"
+    );
+    assert_eq!(
+        floor
+            .display_delta(SizeDelta::from_bytes(-1535))
+            .to_string(),
+        "-1.50 KiB"
+    );
+}
+
+#[test]
+fn size_delta_from_str() {
+    let parse = |s: &str| SizeDelta::from_str(s).map(|delta| delta.bytes());
+    assert_eq!(parse("-5 MB"), Ok(-5_000_000));
+    assert_eq!(parse("+1.20 MiB"), Ok(1_258_291));
+    assert_eq!(parse("\u{2212}512 KiB"), Ok(-524_288));
+    assert_eq!(parse(" - 1 KiB"), Ok(-1024));
+    assert_eq!(parse("42"), Ok(42));
+    assert_eq!(parse("-16 EiB"), Ok(-16 * EXBIBYTE as i128));
+    assert_eq!(
+        parse("-170141183460469231731687303715884105728"),
+        Ok(i128::MIN)
+    );
+    assert_eq!(
+        parse("170141183460469231731687303715884105728")
+            .unwrap_err()
+            .kind(),
+        &ParseErrorKind::OutOfRange
+    );
+    assert_eq!(
+        parse("-2e8 QB").unwrap_err().kind(),
+        &ParseErrorKind::OutOfRange
+    );
+    assert_eq!(
+        parse("--1").unwrap_err().kind(),
+        &ParseErrorKind::Unexpected(Expected::Number)
+    );
+    assert_eq!(parse("--1").unwrap_err().span(), 1..2);
+
+    // round trips through formatting, in either base
+    let delta = SizeDelta::from_bytes(-1_500_000);
+    assert_eq!(SizeDelta::from_str(&format!("{:#}", delta)), Ok(delta));
+
+    let floor =
+        SizeParser::new().with_fractional_bytes(FractionalBytes::Round(RoundingMode::Floor));
+    assert_eq!(
+        floor.parse_delta("0.3 KiB").map(|delta| delta.bytes()),
+        Ok(307)
+    );
+    assert_eq!(
+        floor.parse_delta("-0.3 KiB").map(|delta| delta.bytes()),
+        Ok(-308)
+    );
+}
+
+macro_rules! size_bytes {
+    ($name:ident, $num:expr, $unit:ident, $T:ty, $res:tt) => {
+        #[test]
+        fn $name() {
+            assert_eq!(TypedSize::$unit::<$T>($num).bytes(), $res);
+        }
+    };
+}
+
+size_bytes!(
+    size_bytes_b_i64_1,
+    792633534438178879,
+    Bytes,
+    i64,
+    792633534438178879
+);
+size_bytes!(
+    size_bytes_mb_u64_1,
+    16172825064112138,
+    Kilobytes,
+    u64,
+    16172825064112138000
+);
+size_bytes!(
+    size_bytes_tib_f64_1,
+    1.333602886575971,
+    Tebibytes,
+    f64,
+    1466311880625
+);
+size_bytes!(
+    size_bytes_eib_f64_1,
+    15.999999999999998,
+    Exbibytes,
+    f64,
+    18446744073709549568
+);
+
+#[test]
+fn size_try_bytes() {
+    assert_eq!(
+        TypedSize::Tebibytes(-1.333602886575971).try_bytes(),
+        Err(SizeError::Negative)
+    );
+    assert_eq!(TypedSize::Bytes(-1).try_bytes(), Err(SizeError::Negative));
+    assert_eq!(TypedSize::Bytes(-0.0).try_bytes(), Ok(0));
+    assert_eq!(
+        TypedSize::Bytes(f64::INFINITY).try_bytes(),
+        Err(SizeError::NotFinite)
+    );
+    assert_eq!(
+        TypedSize::Bytes(f64::NEG_INFINITY).try_bytes(),
+        Err(SizeError::NotFinite)
+    );
+    assert_eq!(TypedSize::Exabytes(18.5).try_bytes(), Ok(18_500 * PETABYTE));
+    assert_eq!(
+        TypedSize::Quettabytes(340_282_367.0).try_bytes(),
+        Err(SizeError::Overflow)
+    );
+    assert_eq!(
+        TypedSize::Quettabytes(1.5).try_bytes(),
+        Ok(1500 * RONNABYTE)
+    );
+    assert_eq!(TypedSize::Quebibytes(0.5).try_bytes(), Ok(1 << 99));
+    assert_eq!(
+        TypedSize::Exabytes(1e300).try_bytes(),
+        Err(SizeError::Overflow)
+    );
+    assert_eq!(
+        TypedSize::Exbibytes(u128::MAX).try_bytes(),
+        Err(SizeError::Overflow)
+    );
+    assert_eq!(TypedSize::Bytes(u128::MAX).try_bytes(), Ok(u128::MAX));
+    assert_eq!(TypedSize::Kibibytes(0.3).try_bytes(), Ok(307));
+    assert_eq!(TypedSize::Bytes(f64::MIN_POSITIVE).try_bytes(), Ok(0));
+
+    assert_eq!(TypedSize::Bytes(-1).bytes_saturating(), 0);
+    assert_eq!(TypedSize::Bytes(f64::NAN).bytes_saturating(), 0);
+    assert_eq!(TypedSize::Bytes(f64::NEG_INFINITY).bytes_saturating(), 0);
+    assert_eq!(
+        TypedSize::Bytes(f64::INFINITY).bytes_saturating(),
+        u128::MAX
+    );
+    assert_eq!(
+        TypedSize::Quettabytes(400_000_000).bytes_saturating(),
+        u128::MAX
+    );
+
+    // such sizes can be formatted, but don't convert to a `Size`
+    assert_eq!(
+        TypedSize::Kibibytes(-1).to_string_with(Base::Base2, Style::Smart),
+        "-1.00 KiB"
+    );
+    assert_eq!(format!("{}", TypedSize::Bytes(-512)), "-512 bytes");
+    assert_eq!(
+        format!("{:>10.1}", TypedSize::Kibibytes(-1.5)),
+        "  -1.5 KiB"
+    );
+    assert_eq!(format!("{:#}", TypedSize::Kilobytes(-2)), "-2.00 KB");
+    assert_eq!(format!("{}", TypedSize::Bytes(-0.5)), "0 bytes");
+    assert_eq!(format!("{}", TypedSize::Bytes(i128::MIN)), "-134217728 QiB");
+    assert_eq!(format!("{}", TypedSize::Bytes(f64::INFINITY)), "inf");
+    assert_eq!(format!("{}", TypedSize::Bytes(f64::NEG_INFINITY)), "-inf");
+    assert_eq!(
+        format!("{:>5.1}", TypedSize::Gibibytes(f64::INFINITY)),
+        "  inf"
+    );
+    assert_eq!(format!("{:>5}", TypedSize::Bytes(f64::NAN)), "  NaN");
+    assert_eq!(format!("{}", TypedSize::Bytes(f64::MAX)), "268435456 QiB");
+    assert_eq!(
+        format!("{}", TypedSize::Quettabytes(400_000_000)),
+        "268435456 QiB"
+    );
+    assert_eq!(format!("{:?}", TypedSize::Bytes(-1)), "<size is negative>");
+    assert_eq!(
+        Size::try_from(TypedSize::Quettabytes(400_000_000)),
+        Err(SizeError::Overflow)
+    );
+    assert_eq!(
+        Size::try_from(TypedSize::Bytes(f64::NAN)),
+        Err(SizeError::NotFinite)
+    );
+    assert_eq!(
+        Size::try_from(TypedSize::Kibibytes(1.5)),
+        Ok(Size::from_bytes(1536))
+    );
+    assert!(TypedSize::Bytes(f64::NAN) != TypedSize::Bytes(f64::NAN));
+}
+
+#[test]
+#[allow(deprecated)]
+fn size_variant_constructors() {
+    assert_eq!(Size::Kibibytes(4), Size::from_kib(4));
+    assert_eq!(Size::Gibibytes(1.5), Size::from_mib(1536));
+    assert_eq!(Size::Bytes(42u8).bytes(), 42);
+}
+
+#[test]
+#[allow(deprecated)]
+fn size_to_string_shorthand() {
+    let (base, style) = (Base::Base10, Style::Full);
+    assert_eq!(Size::from_kb(2).to_string(base, style), "2.00 Kilobytes");
+    assert_eq!(
+        TypedSize::Kilobytes(-2).to_string(base, style),
+        "-2.00 Kilobytes"
+    );
+    assert_eq!(
+        Size::from_kb(2).to_string_with(base, style),
+        "2.00 Kilobytes"
+    );
+}
+
+#[test]
+#[should_panic(expected = "size is negative")]
+fn size_bytes_negative() {
+    TypedSize::Kibibytes(-1).bytes();
+}
+
+#[test]
+fn size_constants() {
+    const BLOCK: Size = Size::from_kib(4);
+    const LIMIT: Size = Size::from_unit(3, Unit::Gibibyte);
+    assert_eq!(BLOCK.bytes(), 4096);
+    assert_eq!(LIMIT, Size::from_gib(3));
+    assert_eq!(Size::default(), Size::ZERO);
+    assert_eq!(Size::MAX.bytes(), u128::MAX);
+    assert_eq!(Size::from_eib(16).bytes(), 1 << 64);
+    assert_eq!(Size::from_qib(1 << 27).bytes(), 1 << 127);
+    assert_eq!(format!("{:?}", BLOCK), "4096 bytes");
+    assert!(matches!(TypedSize::from(BLOCK), TypedSize::Bytes(4096)));
+}
+
+#[test]
+#[should_panic(expected = "size is too large")]
+fn size_from_unit_overflow() {
+    let _ = Size::from_qib(1 << 28);
+}
+
+#[test]
+fn large_units() {
+    assert_eq!(format!("{}", Size::from_zib(3)), "3.00 ZiB");
+    assert_eq!(format!("{:#}", Size::from_yb(42)), "42.0 YB");
+    assert_eq!(format!("{:#}", Size::from_rb(999)), "999 RB");
+    assert_eq!(format!("{:#}", Size::from_rb(1000)), "1.00 QB");
+    assert_eq!(
+        Size::from_rib(1024).to_string_with(Base::Base2, Style::Full),
+        "1.00 Quebibytes"
+    );
+    assert_eq!(
+        Size::from_qb(5).to_string_with(Base::Base10, Style::AbbreviatedLowerCase),
+        "5.00 qb"
+    );
+    assert_eq!(format!("{:#}", Size::MAX), "340282367 QB");
+
+    let parse = |s: &str| Size::from_str(s).unwrap();
+    assert_eq!(parse("1.5 ZiB"), Size::from_eib(1536));
+    assert_eq!(parse("2 yottabytes"), Size::from_yb(2));
+    assert_eq!(parse("3 Ronnabyte"), Size::from_rb(3));
+    assert_eq!(parse("4 RiB"), Size::from_rib(4));
+    assert_eq!(parse("0.25 qib"), Size::from_rib(256));
+    assert_eq!(parse("7QB"), Size::from_qb(7));
+}
+
+/// Formats the size one byte short of `$bytes`, which must round up to the next unit.
+macro_rules! format_boundary {
+    ($name:ident, $base:ident, $bytes:expr, $res:tt) => {
+        #[test]
+        fn $name() {
+            let size = Size::from_bytes($bytes - 1);
+            assert_eq!(size.to_string_with(Base::$base, Style::Abbreviated), $res);
+        }
+    };
+}
+
+format_boundary!(boundary_b2_kib, Base2, KIBIBYTE, "1023 B");
+format_boundary!(boundary_b2_10kib, Base2, 10 * KIBIBYTE, "10.0 KiB");
+format_boundary!(boundary_b2_100kib, Base2, 100 * KIBIBYTE, "100 KiB");
+format_boundary!(boundary_b2_mib, Base2, MEBIBYTE, "1.00 MiB");
+format_boundary!(boundary_b2_10mib, Base2, 10 * MEBIBYTE, "10.0 MiB");
+format_boundary!(boundary_b2_100mib, Base2, 100 * MEBIBYTE, "100 MiB");
+format_boundary!(boundary_b2_gib, Base2, GIBIBYTE, "1.00 GiB");
+format_boundary!(boundary_b2_10gib, Base2, 10 * GIBIBYTE, "10.0 GiB");
+format_boundary!(boundary_b2_100gib, Base2, 100 * GIBIBYTE, "100 GiB");
+format_boundary!(boundary_b2_tib, Base2, TEBIBYTE, "1.00 TiB");
+format_boundary!(boundary_b2_10tib, Base2, 10 * TEBIBYTE, "10.0 TiB");
+format_boundary!(boundary_b2_100tib, Base2, 100 * TEBIBYTE, "100 TiB");
+format_boundary!(boundary_b2_pib, Base2, PEBIBYTE, "1.00 PiB");
+format_boundary!(boundary_b2_10pib, Base2, 10 * PEBIBYTE, "10.0 PiB");
+format_boundary!(boundary_b2_100pib, Base2, 100 * PEBIBYTE, "100 PiB");
+format_boundary!(boundary_b2_eib, Base2, EXBIBYTE, "1.00 EiB");
+format_boundary!(boundary_b2_10eib, Base2, 10 * EXBIBYTE, "10.0 EiB");
+format_boundary!(boundary_b2_100eib, Base2, 100 * EXBIBYTE, "100 EiB");
+format_boundary!(boundary_b2_zib, Base2, ZEBIBYTE, "1.00 ZiB");
+format_boundary!(boundary_b2_10zib, Base2, 10 * ZEBIBYTE, "10.0 ZiB");
+format_boundary!(boundary_b2_100zib, Base2, 100 * ZEBIBYTE, "100 ZiB");
+format_boundary!(boundary_b2_yib, Base2, YOBIBYTE, "1.00 YiB");
+format_boundary!(boundary_b2_10yib, Base2, 10 * YOBIBYTE, "10.0 YiB");
+format_boundary!(boundary_b2_100yib, Base2, 100 * YOBIBYTE, "100 YiB");
+format_boundary!(boundary_b2_rib, Base2, ROBIBYTE, "1.00 RiB");
+format_boundary!(boundary_b2_10rib, Base2, 10 * ROBIBYTE, "10.0 RiB");
+format_boundary!(boundary_b2_100rib, Base2, 100 * ROBIBYTE, "100 RiB");
+format_boundary!(boundary_b2_qib, Base2, QUEBIBYTE, "1.00 QiB");
+format_boundary!(boundary_b2_10qib, Base2, 10 * QUEBIBYTE, "10.0 QiB");
+format_boundary!(boundary_b2_100qib, Base2, 100 * QUEBIBYTE, "100 QiB");
+format_boundary!(boundary_b2_1024qib, Base2, 1024 * QUEBIBYTE, "1024 QiB");
+format_boundary!(boundary_b10_kb, Base10, KILOBYTE, "999 B");
+format_boundary!(boundary_b10_10kb, Base10, 10 * KILOBYTE, "10.0 KB");
+format_boundary!(boundary_b10_100kb, Base10, 100 * KILOBYTE, "100 KB");
+format_boundary!(boundary_b10_mb, Base10, MEGABYTE, "1.00 MB");
+format_boundary!(boundary_b10_10mb, Base10, 10 * MEGABYTE, "10.0 MB");
+format_boundary!(boundary_b10_100mb, Base10, 100 * MEGABYTE, "100 MB");
+format_boundary!(boundary_b10_gb, Base10, GIGABYTE, "1.00 GB");
+format_boundary!(boundary_b10_10gb, Base10, 10 * GIGABYTE, "10.0 GB");
+format_boundary!(boundary_b10_100gb, Base10, 100 * GIGABYTE, "100 GB");
+format_boundary!(boundary_b10_tb, Base10, TERABYTE, "1.00 TB");
+format_boundary!(boundary_b10_10tb, Base10, 10 * TERABYTE, "10.0 TB");
+format_boundary!(boundary_b10_100tb, Base10, 100 * TERABYTE, "100 TB");
+format_boundary!(boundary_b10_pb, Base10, PETABYTE, "1.00 PB");
+format_boundary!(boundary_b10_10pb, Base10, 10 * PETABYTE, "10.0 PB");
+format_boundary!(boundary_b10_100pb, Base10, 100 * PETABYTE, "100 PB");
+format_boundary!(boundary_b10_eb, Base10, EXABYTE, "1.00 EB");
+format_boundary!(boundary_b10_10eb, Base10, 10 * EXABYTE, "10.0 EB");
+format_boundary!(boundary_b10_100eb, Base10, 100 * EXABYTE, "100 EB");
+format_boundary!(boundary_b10_zb, Base10, ZETTABYTE, "1.00 ZB");
+format_boundary!(boundary_b10_10zb, Base10, 10 * ZETTABYTE, "10.0 ZB");
+format_boundary!(boundary_b10_100zb, Base10, 100 * ZETTABYTE, "100 ZB");
+format_boundary!(boundary_b10_yb, Base10, YOTTABYTE, "1.00 YB");
+format_boundary!(boundary_b10_10yb, Base10, 10 * YOTTABYTE, "10.0 YB");
+format_boundary!(boundary_b10_100yb, Base10, 100 * YOTTABYTE, "100 YB");
+format_boundary!(boundary_b10_rb, Base10, RONNABYTE, "1.00 RB");
+format_boundary!(boundary_b10_10rb, Base10, 10 * RONNABYTE, "10.0 RB");
+format_boundary!(boundary_b10_100rb, Base10, 100 * RONNABYTE, "100 RB");
+format_boundary!(boundary_b10_qb, Base10, QUETTABYTE, "1.00 QB");
+format_boundary!(boundary_b10_10qb, Base10, 10 * QUETTABYTE, "10.0 QB");
+format_boundary!(boundary_b10_100qb, Base10, 100 * QUETTABYTE, "100 QB");
+format_boundary!(boundary_b10_1000qb, Base10, 1000 * QUETTABYTE, "1000 QB");
+
+#[test]
+fn rounding_carry_with_precision() {
+    assert_eq!("1 MiB", format!("{:.0}", Size::from_bytes(MEBIBYTE - 1)));
+    assert_eq!(
+        "1023.999 KiB",
+        format!("{:.3}", Size::from_bytes(MEBIBYTE - 1))
+    );
+    assert_eq!(
+        "10 KiB",
+        format!("{:.0}", Size::from_bytes(10 * KIBIBYTE - 1))
+    );
+    assert_eq!(
+        "9.99 KiB",
+        format!("{}", Size::from_bytes(10 * KIBIBYTE - 6))
+    );
+}
+
+/// Formats `bytes` like the rule tables did before `SizeFormatter`: picking the unit and
+/// decimal places by the number of bytes, dividing as `f64` and leaving the rounding to
+/// `{:.*}`. Returns `None` for sizes that then carried into a larger unit, e.g. "10.00 KiB"
+/// or "1024 KiB", which are now printed in that unit.
+fn old_format(bytes: u64, base: Base) -> Option<String> {
+    let (symbols, step): (_, u64) = match base {
+        Base::Base10 => (["KB", "MB", "GB", "TB", "PB", "EB"], 1000),
+        _ => (["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"], 1024),
+    };
+    let (mut symbol, mut divisor) = (symbols[0], step);
+    for &next in symbols[1..].iter() {
+        if bytes / divisor < step {
+            break;
+        }
+        symbol = next;
+        divisor *= step;
+    }
+    let (decimals, less_than) = match bytes / divisor {
+        0..=9 => (2, 10.0),
+        10..=99 => (1, 100.0),
+        _ => (0, step as f64),
+    };
+    let number = format!("{:.*}", decimals, bytes as f64 / divisor as f64);
+    match number.parse::<f64>().unwrap() < less_than {
+        true => Some(format!("{} {}", number, symbol)),
+        false => None,
+    }
+}
+
+#[test]
+fn rounding_parity() {
+    // By default, sizes are printed exactly like they used to be
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    let random = (0..20_000).map(|i| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state >> (i % 54)
+    });
+    let sizes = (1000..200_000).chain(random);
+    for bytes in sizes.filter(|&bytes| bytes >= 1024) {
+        for &base in [Base::Base2, Base::Base10].iter() {
+            if let Some(expected) = old_format(bytes, base) {
+                let formatter = SizeFormatter::new().with_base(base);
+                let size = Size::from_bytes(bytes.into());
+                assert_eq!(formatter.display(size).to_string(), expected);
+            }
+        }
+    }
+    let formatter = SizeFormatter::new().with_base(Base::Base10);
+    assert_eq!(
+        "7.63 KB",
+        formatter.display(Size::from_bytes(7635)).to_string()
+    );
+    assert_eq!(
+        "2.67 KB",
+        formatter.display(Size::from_bytes(2665)).to_string()
+    );
+    assert_eq!(
+        "1.01 KB",
+        formatter.display(Size::from_bytes(1015)).to_string()
+    );
+
+    // The other modes round the exact number of bytes, as if it were scaled before dividing
+    let modes = [
+        (
+            RoundingMode::HalfEvenExact,
+            f64::round_ties_even as fn(f64) -> f64,
+        ),
+        (RoundingMode::HalfUp, f64::round),
+        (RoundingMode::Floor, f64::floor),
+        (RoundingMode::Ceil, f64::ceil),
+        (RoundingMode::Truncate, f64::trunc),
+    ];
+    for &(rounding, round) in modes.iter() {
+        let formatter = SizeFormatter::new()
+            .with_base(Base::Base10)
+            .with_rounding(rounding);
+        for bytes in 1000..9990 {
+            let expected = format!("{:.2} KB", round(bytes as f64 * 100.0 / 1000.0) / 100.0);
+            assert_eq!(
+                formatter.display(Size::from_bytes(bytes)).to_string(),
+                expected
+            );
+        }
+    }
+    let formatter = formatter.with_rounding(RoundingMode::HalfEvenExact);
+    assert_eq!(
+        "7.64 KB",
+        formatter.display(Size::from_bytes(7635)).to_string()
+    );
+    assert_eq!(
+        "2.66 KB",
+        formatter.display(Size::from_bytes(2665)).to_string()
+    );
+    assert_eq!(
+        "1.02 KB",
+        formatter.display(Size::from_bytes(1015)).to_string()
+    );
+
+    // Scientific notation rounds the same way
+    for &rounding in [RoundingMode::HalfEven, RoundingMode::HalfEvenExact].iter() {
+        let formatter = SizeFormatter::new()
+            .with_base(Base::Base10)
+            .with_rounding(rounding);
+        let scientific = SizeFormatter::new()
+            .with_base(Base::Base10)
+            .with_rounding(rounding)
+            .with_scientific_notation(0);
+        for bytes in (1005..9990).step_by(10) {
+            let exact = formatter.display(Size::from_bytes(bytes)).to_string();
+            let float = scientific.display(Size::from_bytes(bytes)).to_string();
+            assert_eq!(float, format!("{}e0 KB", &exact[..4]));
+        }
+    }
+}
+
+#[test]
+fn rounding_modes() {
+    let format = |rounding, bytes| {
+        let formatter = SizeFormatter::new().with_rounding(rounding);
+        formatter.display(Size::from_bytes(bytes)).to_string()
+    };
+
+    // 1.125 KiB is a tie at two decimal places
+    assert_eq!("1.12 KiB", format(RoundingMode::HalfEven, 1152));
+    assert_eq!("1.13 KiB", format(RoundingMode::HalfUp, 1152));
+    assert_eq!("1.12 KiB", format(RoundingMode::Floor, 1152));
+    assert_eq!("1.13 KiB", format(RoundingMode::Ceil, 1152));
+    assert_eq!("1.12 KiB", format(RoundingMode::Truncate, 1152));
+
+    // A single byte over is enough to round up
+    assert_eq!("1.00 KiB", format(RoundingMode::HalfUp, 1025));
+    assert_eq!("1.01 KiB", format(RoundingMode::Ceil, 1025));
+    assert_eq!("2.00 KiB", format(RoundingMode::Ceil, 2048));
+    assert_eq!(
+        "1.10 KB",
+        SizeFormatter::new()
+            .with_base(Base::Base10)
+            .with_rounding(RoundingMode::Ceil)
+            .display(Size::from_bytes(1100))
+            .to_string()
+    );
+
+    // Rounding up carries over into the next unit, rounding down never does
+    assert_eq!("1.00 MiB", format(RoundingMode::Ceil, 1023 * KIBIBYTE + 1));
+    assert_eq!("1023 KiB", format(RoundingMode::Floor, MEBIBYTE - 1));
+    assert_eq!(
+        "9.99 KiB",
+        format(RoundingMode::Truncate, 10 * KIBIBYTE - 1)
+    );
+}
+
+#[test]
+fn precision_policies() {
+    let format =
+        |formatter: &SizeFormatter, bytes| formatter.display(Size::from_bytes(bytes)).to_string();
+
+    let formatter = SizeFormatter::new().with_precision(Precision::SignificantFigures(2));
+    assert_eq!("2.0 MiB", format(&formatter, 2 * MEBIBYTE));
+    assert_eq!("20 MiB", format(&formatter, 20 * MEBIBYTE));
+    assert_eq!("200 MiB", format(&formatter, 200 * MEBIBYTE));
+    assert_eq!("10 KiB", format(&formatter, 10 * KIBIBYTE - 1));
+    assert_eq!("999 bytes", format(&formatter, 999));
+
+    // Rounding that carries past the figures asked for moves on to the next unit
+    assert_eq!("0.98 GiB", format(&formatter, 999 * MEBIBYTE));
+    assert_eq!(
+        "0.98 GiB",
+        format(&formatter, 999 * MEBIBYTE + MEBIBYTE / 2)
+    );
+    assert_eq!("990 MiB", format(&formatter, 994 * MEBIBYTE));
+    assert_eq!("1.0 MiB", format(&formatter, MEBIBYTE - 1));
+    let formatter = SizeFormatter::new().with_precision(Precision::SignificantFigures(3));
+    assert_eq!("0.977 MiB", format(&formatter, 1000 * KIBIBYTE));
+    assert_eq!("0.976 MiB", format(&formatter, 999_900 * KIBIBYTE / 1000));
+    assert_eq!("100 KiB", format(&formatter, 99_960 * KIBIBYTE / 1000));
+    let formatter = SizeFormatter::new().with_precision(Precision::SignificantFigures(4));
+    assert_eq!("1000 KiB", format(&formatter, 1000 * KIBIBYTE));
+    let formatter = SizeFormatter::new().with_precision(Precision::SignificantFigures(0));
+    assert_eq!("2 MiB", format(&formatter, 2 * MEBIBYTE));
+    assert_eq!("9 MiB", format(&formatter, 9 * MEBIBYTE + 1));
+    assert_eq!("1 GiB", format(&formatter, 999 * MEBIBYTE));
+
+    let formatter = SizeFormatter::new().with_precision(Precision::Decimals(3));
+    assert_eq!("2.000 MiB", format(&formatter, 2 * MEBIBYTE));
+    assert_eq!("200.000 MiB", format(&formatter, 200 * MEBIBYTE));
+    let formatter = SizeFormatter::new().with_precision(Precision::Decimals(30));
+    assert_eq!(
+        "1.500000000000000000000000000000 KiB",
+        format(&formatter, 1536)
+    );
+    assert_eq!(
+        format!("{:.40}", Size::from_kib(1)),
+        format!("1.{:0<40} KiB", "")
+    );
+
+    // Precisions past the 38 digits of a `u128` are never wrapped around or rounded as f64
+    assert_eq!(
+        format!("{:.400}", Size::from_bytes(1536)),
+        format!("1.5{:0<399} KiB", "")
+    );
+    assert_eq!(
+        format!("{:.30}", Size::MAX),
+        "268435455.999999999999999999999999999999 QiB"
+    );
+    assert_eq!(
+        format!("{:.38}", Size::MAX),
+        format!("268435456.{:0<38} QiB", "")
+    );
+    for &digits in [40, usize::MAX].iter() {
+        let formatter = SizeFormatter::new().with_precision(Precision::SignificantFigures(digits));
+        assert_eq!(format!("1.5{:0<36} KiB", ""), format(&formatter, 1536));
+    }
+
+    let formatter = SizeFormatter::new().with_trailing_zeros(false);
+    assert_eq!("2 MiB", format(&formatter, 2 * MEBIBYTE));
+    assert_eq!("2.5 MiB", format(&formatter, 2 * MEBIBYTE + MEBIBYTE / 2));
+    assert_eq!("20 MiB", format(&formatter, 20 * MEBIBYTE));
+    assert_eq!("2.25 MiB", format(&formatter, 2 * MEBIBYTE + MEBIBYTE / 4));
+
+    // Applies the same way to both bases
+    let formatter = formatter.with_base(Base::Base10);
+    assert_eq!("2 MB", format(&formatter, 2 * MEGABYTE));
+    assert_eq!("2.5 MB", format(&formatter, 2 * MEGABYTE + MEGABYTE / 2));
+}
+
+#[test]
+fn scientific_notation() {
+    let rules = [
+        FormatRule::new(KILOBYTE, Unit::Byte, 0),
+        FormatRule::new(u128::MAX, Unit::Kilobyte, 2),
+    ];
+    let formatter = SizeFormatter::new()
+        .with_rules(FormatRules::new(&rules))
+        .with_scientific_notation(4);
+    let format =
+        |formatter: &SizeFormatter, bytes| formatter.display(Size::from_bytes(bytes)).to_string();
+
+    assert_eq!("9999.00 KB", format(&formatter, 9_999_000));
+    assert_eq!("1.23e4 KB", format(&formatter, 12_345_678));
+    assert_eq!("1.00e5 KB", format(&formatter, 99_999_999));
+    assert_eq!("4.20e15 KB", format(&formatter, 4200 * PETABYTE));
+
+    let formatter = formatter
+        .with_precision(Precision::SignificantFigures(4))
+        .with_trailing_zeros(false);
+    assert_eq!("1.235e4 KB", format(&formatter, 12_345_678));
+    assert_eq!("1e5 KB", format(&formatter, 100_000_000));
+
+    let formatter = SizeFormatter::new().with_scientific_notation(0);
+    assert_eq!(
+        format!("{:.45}", formatter.display(Size::from_bytes(1536))),
+        format!("1.5{:0<44}e0 KiB", "")
+    );
+    let formatter = formatter.with_precision(Precision::SignificantFigures(usize::MAX));
+    assert_eq!(format(&formatter, 1536), format!("1.5{:0<36}e0 KiB", ""));
+    let formatter = SizeFormatter::new().with_scientific_notation(u32::MAX);
+    assert_eq!("1.50 KiB", format(&formatter, 1536));
+}
+
+#[test]
+fn exact_integer_formatting() {
+    let format = |rounding, bytes| {
+        let formatter = SizeFormatter::new().with_rounding(rounding);
+        formatter.display(Size::from_bytes(bytes)).to_string()
+    };
+
+    // 2^53 + 1 bytes can't be represented as an f64, but the extra byte still rounds up
+    assert_eq!("8.00 PiB", format(RoundingMode::HalfEven, (1 << 53) + 1));
+    assert_eq!("8.01 PiB", format(RoundingMode::Ceil, (1 << 53) + 1));
+    assert_eq!("16.0 EiB", format(RoundingMode::HalfEven, u64::MAX.into()));
+    assert_eq!("15.9 EiB", format(RoundingMode::Floor, u64::MAX.into()));
+    assert_eq!("268435456 QiB", format(RoundingMode::HalfEven, u128::MAX));
+    assert_eq!("268435455 QiB", format(RoundingMode::Floor, u128::MAX));
+
+    let size = Size::from_bytes(u64::MAX.into());
+    assert_eq!("16 EiB", format!("{:.0}", size));
+    assert_eq!("15.9999999999999999991 EiB", format!("{:.19}", size));
+
+    // More decimal places than the digits of a u64 hold
+    let rules = [FormatRule::new(u128::MAX, Unit::Kibibyte, 2)];
+    let formatter = SizeFormatter::new().with_rules(FormatRules::new(&rules));
+    assert_eq!(
+        format!("{:.21}", formatter.display(Size::ZERO)),
+        format!("0.{:0<21} KiB", "")
+    );
+    let rules = [FormatRule::new(u128::MAX, Unit::Mebibyte, 2)];
+    let formatter = SizeFormatter::new()
+        .with_rules(FormatRules::new(&rules))
+        .with_precision(Precision::Decimals(25));
+    assert_eq!(
+        formatter.display(Size::from_bytes(1)).to_string(),
+        "0.0000009536743164062500000 MiB"
+    );
+}
+
+#[test]
+fn size_from_str() {
+    let size = Size::from_str("200");
+    assert_eq!(size.unwrap(), Size::from_bytes(200));
+
+    let size = Size::from_str("200 bytes");
+    assert_eq!(size.unwrap(), Size::from_bytes(200));
+
+    let size = Size::from_str("200 KiB");
+    assert_eq!(size.unwrap(), Size::from_kib(200));
+
+    let size = Size::from_str("2.50 MiB");
+    assert_eq!(size.unwrap(), Size::from_kib(2560));
+
+    let size = Size::from_str("2G");
+    assert_eq!(size.unwrap(), Size::from_gb(2));
+
+    let size = Size::from_str("~");
+    let kind = ParseErrorKind::Unexpected(Expected::Number);
+    assert_eq!(size.unwrap_err().kind(), &kind);
+
+    let size = TypedSize::<f64>::from_str("4.06 EiB").unwrap();
+    assert_eq!(size, TypedSize::Bytes::<i64>(4680861308703798272));
+}
+
+#[test]
+fn size_from_str_longest_match() {
+    let parse = |s: &str| Size::from_str(s).map(|size| size.bytes());
+
+    assert_eq!(parse("1e3").unwrap(), 1000);
+    assert_eq!(parse("1e3k").unwrap(), 1_000_000);
+    assert_eq!(parse("1eb").unwrap(), EXABYTE);
+    assert_eq!(parse("1E").unwrap(), EXABYTE);
+    assert_eq!(parse("  1.5   Kibibytes  ").unwrap(), 1536);
+    assert_eq!(parse("1 KiB").unwrap(), KIBIBYTE);
+    assert_eq!(parse("1 kib").unwrap(), KIBIBYTE);
+    assert_eq!(parse("1 Ki").unwrap(), KIBIBYTE);
+
+    // Abbreviations spell the trailing b in the same case as the prefix
+    assert!(parse("1 Kb").is_err());
+    assert!(parse("1 kB").is_err());
+    assert!(parse("1 bytess").is_err());
+    assert!(parse("1e+").is_err());
+    assert!(parse("1\t").is_err());
+    let kind = |s: &str| Size::from_str(s).unwrap_err().kind().clone();
+    assert_eq!(kind(""), ParseErrorKind::Unexpected(Expected::Number));
+    assert_eq!(kind("1..2 KB"), ParseErrorKind::InvalidNumber);
+}
+
+#[test]
+fn size_parse_bytes() {
+    use std::ffi::OsStr;
+
+    let size = Size::parse_bytes(b"200 KiB").unwrap();
+    assert_eq!(size, Size::from_kib(200));
+    assert!(Size::parse_bytes(b"200 \xff").is_err());
+    assert!(Size::parse_bytes(b"200 KiB\0").is_err());
+
+    let parser = SizeParser::new().with_lenient_input(true);
+    let size = parser.parse_bytes("4\u{a0}GiB".as_bytes()).unwrap();
+    assert_eq!(size, Size::from_gib(4));
+    assert!(parser.parse_bytes(b"4\xa0GiB").is_err());
+
+    let size = Size::parse_os_str(OsStr::new("2.50 MiB")).unwrap();
+    assert_eq!(size, Size::from_kib(2560));
+}
+
+#[test]
+fn parse_errors() {
+    let parse = |s: &str| Size::from_str(s).unwrap_err();
+
+    let err = parse("  ");
+    assert_eq!(err.kind(), &ParseErrorKind::Unexpected(Expected::Number));
+    assert_eq!((err.span(), err.found()), (2..2, ""));
+    assert_eq!(err.to_string(), "expected a number, found end of input");
+
+    let err = parse("1..2 KB");
+    assert_eq!((err.span(), err.found()), (0..4, "1..2"));
+    assert_eq!(err.to_string(), "invalid number `1..2` at offset 0");
+
+    let err = parse("1 KiB 5");
+    assert_eq!(err.kind(), &ParseErrorKind::Unexpected(Expected::End));
+    assert_eq!(
+        err.to_string(),
+        "expected end of input, found `5` at offset 6"
+    );
+
+    // Misspelled units are blamed as a whole, not from where they stop matching
+    let err = parse("10 kilobites");
+    assert_eq!(err.kind(), &ParseErrorKind::Unexpected(Expected::Unit));
+    assert_eq!((err.span(), err.suggestion()), (3..12, Some("KB")));
+
+    let err = parse("1 é");
+    assert_eq!(
+        (err.span(), err.found(), err.suggestion()),
+        (2..4, "é", None)
+    );
+    assert_eq!(format!("{:#}", err).lines().last(), Some("  ^"));
+
+    let suggestion = |s: &str| parse(s).suggestion();
+    assert_eq!(suggestion("1 Mbi"), Some("MiB"));
+    assert_eq!(suggestion("4 gigs"), Some("GB"));
+    assert_eq!(suggestion("4 megs"), Some("MB"));
+    assert_eq!(suggestion("1 kB"), Some("KB"));
+    assert_eq!(suggestion("1 gibibites"), Some("GiB"));
+    assert_eq!(suggestion("1 TIB"), Some("TiB"));
+    assert_eq!(suggestion("1 potato"), None);
+    assert_eq!(suggestion("1 x"), None);
+}
+
+#[test]
+fn parse_out_of_range() {
+    let err = TypedSize::<u8>::from_str("300 KB").unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::OutOfRange);
+    assert_eq!((err.span(), err.found()), (0..3, "300"));
+    assert_eq!(
+        err.to_string(),
+        "value `300` at offset 0 is out of range for the target type"
+    );
+    assert_eq!(
+        TypedSize::<u8>::from_str("255 KB").unwrap(),
+        TypedSize::Kilobytes(255)
+    );
+
+    let err = Size::from_str(" 1e999 KB").unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::OutOfRange);
+    assert_eq!(err.span(), 1..6);
+    let err = Size::from_str("1e99999999999999999999").unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::OutOfRange);
+    let err = Size::from_str("300000000 QiB").unwrap_err();
+    assert_eq!(
+        (err.kind(), err.span()),
+        (&ParseErrorKind::OutOfRange, 0..9)
+    );
+    assert!(matches!(
+        TypedSize::<u64>::from_str("300000000 QiB"),
+        Ok(TypedSize::Quebibytes(300000000))
+    ));
+
+    // There's no sign in the grammar, so negative sizes are rejected as well
+    let err = Size::from_str("-1 KB").unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::Unexpected(Expected::Number));
+}
+
+#[test]
+fn parse_exact() {
+    let parse = |s: &str| Size::from_str(s).unwrap().bytes();
+
+    assert_eq!(parse("1.5 KiB"), 1536);
+    assert_eq!(parse("18446744073709551615 B"), u64::MAX.into());
+    assert_eq!(
+        parse("340282366920938463463374607431768211455.0"),
+        u128::MAX
+    );
+    assert_eq!(parse("0.1 EiB"), EXBIBYTE / 10 + 1);
+    assert_eq!(parse("15.999999999999999999 EiB"), u64::MAX.into());
+    assert_eq!(parse("0.1 QB"), 100 * RONNABYTE);
+    assert_eq!(parse("1e-3 MB"), 1000);
+    assert_eq!(parse("0.000000000000000000000000000000000000000000000000000000000000000000000000000000000001 EB"), 0);
+    assert_eq!(parse("0e-99999999999999999999 KB"), 0);
+    assert_eq!(parse("00000000000000000000000000000000000000000000000000000000000000000000000000000000012 KB"), 12_000);
+    assert_eq!(
+        TypedSize::<f64>::from_str("2.5 MiB").unwrap(),
+        TypedSize::Bytes(2.5 * MEBIBYTE as f64)
+    );
+
+    // Sizes that are whole in their own unit keep it
+    assert!(matches!(
+        TypedSize::<u64>::from_str("2.0 KiB"),
+        Ok(TypedSize::Kibibytes(2))
+    ));
+    assert!(matches!(
+        TypedSize::<u64>::from_str("1e3 KB"),
+        Ok(TypedSize::Kilobytes(1000))
+    ));
+    assert!(matches!(
+        TypedSize::<u64>::from_str("0.5 KB"),
+        Ok(TypedSize::Bytes(500))
+    ));
+}
+
+#[test]
+fn parse_fractional_bytes() {
+    let parse = |fractional_bytes, s: &str| {
+        let parser = SizeParser::new().with_fractional_bytes(fractional_bytes);
+        parser.parse(s).map(|size| size.bytes())
+    };
+    let round = |rounding, s| parse(FractionalBytes::Round(rounding), s).unwrap();
 
-    let size = Size::<f64>::from_str("~");
-    assert_eq!(format!("{:?}", size.err().unwrap()), "TokenError(Unmatch)");
+    assert_eq!(round(RoundingMode::HalfEven, "2.5 B"), 2);
+    assert_eq!(round(RoundingMode::HalfEven, "3.5 B"), 4);
+    assert_eq!(round(RoundingMode::HalfEven, "2.50000000000000000001 B"), 3);
+    assert_eq!(round(RoundingMode::HalfUp, "2.5 B"), 3);
+    assert_eq!(round(RoundingMode::Floor, "0.3 KiB"), 307);
+    assert_eq!(round(RoundingMode::Ceil, "0.3 KiB"), 308);
+    assert_eq!(round(RoundingMode::Ceil, "1e-30 B"), 1);
+    assert_eq!(round(RoundingMode::Ceil, "1e-300 B"), 1);
+    assert_eq!(round(RoundingMode::Truncate, "1.9 B"), 1);
 
-    let size = Size::<f64>::from_str("4.06 EiB").unwrap();
-    assert_eq!(size, Size::Bytes::<i64>(4680861308703798272));
+    let err = parse(FractionalBytes::Reject, " 0.3 KiB ").unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::FractionalBytes);
+    assert_eq!((err.span(), err.found()), (1..8, "0.3 KiB"));
+    assert_eq!(parse(FractionalBytes::Reject, "0.5 KiB").unwrap(), 512);
 }
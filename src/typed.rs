@@ -0,0 +1,489 @@
+//! A size given as a number of some unit, of any numeric type.
+//!
+//! [`TypedSize`] is how sizes that aren't a whole number of bytes, or that come in a
+//! particular unit, are written down: `TypedSize::Gibibytes(1.5)`. Converting it to a
+//! [`Size`] counts its bytes exactly, and is where negative, non-finite and overly large
+//! values are caught.
+
+use crate::ops::Scalar;
+use crate::DEFAULT_BASE;
+use crate::{Base, ParseError, Size, SizeError, SizeFormatter, SizeParser, Style, Unit};
+use num_traits::float::FloatCore;
+use num_traits::FromPrimitive;
+use num_traits::ToPrimitive;
+use std::cmp::{Ordering, Reverse};
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+#[derive(Clone, Copy)]
+pub enum TypedSize<T> {
+    Bytes(T),
+    Kibibytes(T),
+    Kilobytes(T),
+    Mebibytes(T),
+    Megabytes(T),
+    Gibibytes(T),
+    Gigabytes(T),
+    Tebibytes(T),
+    Terabytes(T),
+    Pebibytes(T),
+    Petabytes(T),
+    Exbibytes(T),
+    Exabytes(T),
+    Zettabytes(T),
+    Zebibytes(T),
+    Yottabytes(T),
+    Yobibytes(T),
+    Ronnabytes(T),
+    Robibytes(T),
+    Quettabytes(T),
+    Quebibytes(T),
+}
+
+impl<T> fmt::Display for TypedSize<T>
+where
+    T: ToPrimitive,
+{
+    /// Formats the size like a [`Size`] of the same number of bytes, with a minus sign if
+    /// it is negative (e.g. "-1.00 KiB"). Finite sizes of more than `u128::MAX` bytes
+    /// either way are clamped to that, and NaN and infinities are printed like an `f64`
+    /// ("NaN", "inf" and "-inf").
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let base = match fmt.alternate() {
+            true => Base::Base10,
+            false => DEFAULT_BASE,
+        };
+        self.fmt_with(&SizeFormatter::new().with_base(base), fmt)
+    }
+}
+
+impl<T> fmt::Debug for TypedSize<T>
+where
+    T: ToPrimitive,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.try_bytes() {
+            Ok(bytes) => write!(fmt, "{} bytes", bytes),
+            Err(error) => write!(fmt, "<{}>", error),
+        }
+    }
+}
+
+impl<T, U> PartialEq<TypedSize<U>> for TypedSize<T>
+where
+    T: ToPrimitive,
+    U: ToPrimitive,
+{
+    /// Sizes are equal if they are exactly the same amount of bytes, regardless of their
+    /// units and including fractions of a byte, negative sizes and sizes of more than
+    /// `u128::MAX` bytes. NaN isn't equal to anything.
+    fn eq(&self, other: &TypedSize<U>) -> bool {
+        match (self.exact(), other.exact()) {
+            (Some(exact), Some(other)) => exact == other,
+            _ => false,
+        }
+    }
+}
+
+impl<T> Eq for TypedSize<T> where T: ToPrimitive + Eq {}
+
+impl<T, U> PartialOrd<TypedSize<U>> for TypedSize<T>
+where
+    T: ToPrimitive,
+    U: ToPrimitive,
+{
+    /// Sizes are ordered by their exact amount of bytes, with negative sizes below zero.
+    ///
+    /// ```
+    /// use size::TypedSize;
+    ///
+    /// assert!(TypedSize::Kilobytes(1) < TypedSize::Kibibytes(1));
+    /// assert!(TypedSize::Bytes(-1) < TypedSize::Bytes(0));
+    /// assert!(TypedSize::Kibibytes(-1) < TypedSize::Bytes(-1000));
+    /// assert!(TypedSize::Kibibytes(1.5) > TypedSize::Bytes(1024));
+    /// ```
+    fn partial_cmp(&self, other: &TypedSize<U>) -> Option<Ordering> {
+        match (self.exact(), other.exact()) {
+            (Some(exact), Some(other)) => Some(exact.cmp(&other)),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Ord for TypedSize<T>
+where
+    T: ToPrimitive + Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.exact().cmp(&other.exact())
+    }
+}
+
+impl<T> Hash for TypedSize<T>
+where
+    T: ToPrimitive + Eq,
+{
+    /// Hashes the exact amount of bytes, so that equal sizes in different units hash the
+    /// same.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.exact().hash(state);
+    }
+}
+
+/// The exact amount of bytes in a size, which is how sizes are compared. Negative sizes
+/// order in reverse of their magnitude, below zero and all positive sizes.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Exact {
+    Negative(Reverse<Magnitude>),
+    Positive(Magnitude),
+}
+
+/// An amount of bytes without its sign. A finite amount is `bits * 2^(top - 256)`, with
+/// `bits` shifted left until its highest bit is set (or zero for zero bytes), so that
+/// every amount has a single representation and the derived ordering compares the
+/// position of the highest bit first.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Magnitude {
+    Finite { top: i32, bits: (u128, u128) },
+    Infinite,
+}
+
+impl Magnitude {
+    /// Returns the magnitude of `value * multiplier * 2^exponent`.
+    fn new(value: u128, multiplier: u128, exponent: i32) -> Self {
+        let (high, low) = widening_mul(value, multiplier);
+        let shift = match high {
+            0 => 128 + low.leading_zeros(),
+            _ => high.leading_zeros(),
+        };
+        let bits = match shift {
+            256 => {
+                return Magnitude::Finite {
+                    top: i32::MIN,
+                    bits: (0, 0),
+                }
+            }
+            128.. => (low << (shift - 128), 0),
+            0 => (high, low),
+            _ => ((high << shift) | (low >> (128 - shift)), low << shift),
+        };
+        let top = exponent + 256 - shift as i32;
+        Magnitude::Finite { top, bits }
+    }
+}
+
+/// Multiplies two `u128`s into a 256 bit product, returned as its high and low halves.
+fn widening_mul(x: u128, y: u128) -> (u128, u128) {
+    const LOW: u128 = u64::MAX as u128;
+    let (x1, x0, y1, y0) = (x >> 64, x & LOW, y >> 64, y & LOW);
+    let (middle, carry) = (x1 * y0).overflowing_add(x0 * y1);
+    let (low, overflow) = (x0 * y0).overflowing_add(middle << 64);
+    let high = x1 * y1 + (middle >> 64) + ((carry as u128) << 64) + overflow as u128;
+    (high, low)
+}
+
+impl<T> TypedSize<T>
+where
+    T: ToPrimitive,
+{
+    /// Returns the number of bytes in the size, truncating a fractional byte count
+    /// towards zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size is negative, not finite or more than `u128::MAX` bytes. Use
+    /// [`TypedSize::try_bytes()`] or [`TypedSize::bytes_saturating()`] if that is possible.
+    pub fn bytes(&self) -> u128 {
+        match self.try_bytes() {
+            Ok(bytes) => bytes,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Returns the number of bytes in the size, truncating a fractional byte count
+    /// towards zero, or why it doesn't fit in a `u128`. The conversion is exact: unlike
+    /// multiplying in `f64`, `TypedSize::Exbibytes(15.5)` is exactly 15.5 * 2^60 bytes.
+    ///
+    /// ```
+    /// use size::{SizeError, TypedSize};
+    ///
+    /// assert_eq!(TypedSize::Kibibytes(1.5).try_bytes(), Ok(1536));
+    /// assert_eq!(TypedSize::Kibibytes(-1).try_bytes(), Err(SizeError::Negative));
+    /// assert_eq!(TypedSize::Kibibytes(f64::NAN).try_bytes(), Err(SizeError::NotFinite));
+    /// assert_eq!(TypedSize::Quebibytes(1 << 28).try_bytes(), Err(SizeError::Overflow));
+    /// ```
+    pub fn try_bytes(&self) -> Result<u128, SizeError> {
+        let (value, unit) = self.as_unit();
+        let multiplier = unit.bytes_per_unit();
+        let bytes = match Scalar::new(value) {
+            Scalar::Integer { negative: true, .. } => return Err(SizeError::Negative),
+            Scalar::Integer { magnitude, .. } => magnitude.checked_mul(multiplier),
+            Scalar::Float(value) if !value.is_finite() => return Err(SizeError::NotFinite),
+            Scalar::Float(value) if value < 0.0 => return Err(SizeError::Negative),
+            Scalar::Float(value) => {
+                // The value is exactly `mantissa * 2^exponent`, with a 53 bit mantissa.
+                // Only the odd part of the unit is multiplied in, which is at most 5^30
+                // (for quettabytes), so the product can't overflow.
+                let (mantissa, exponent, _) = value.integer_decode();
+                let twos = multiplier.trailing_zeros();
+                let product = u128::from(mantissa) * (multiplier >> twos);
+                let exponent = i32::from(exponent) + twos as i32;
+                let shift = exponent.unsigned_abs();
+                match exponent < 0 {
+                    true => Some(product.checked_shr(shift).unwrap_or(0)),
+                    false if product.leading_zeros() < shift => None,
+                    false => Some(product << shift),
+                }
+            }
+        };
+        bytes.ok_or(SizeError::Overflow)
+    }
+
+    /// Returns the exact amount of bytes in the size, or `None` for NaN.
+    fn exact(&self) -> Option<Exact> {
+        let (value, unit) = self.as_unit();
+        let multiplier = unit.bytes_per_unit();
+        let (negative, magnitude) = match Scalar::new(value) {
+            Scalar::Integer {
+                negative,
+                magnitude,
+            } => (negative, Magnitude::new(magnitude, multiplier, 0)),
+            Scalar::Float(value) if value.is_nan() => return None,
+            Scalar::Float(value) if value.is_infinite() => (value < 0.0, Magnitude::Infinite),
+            Scalar::Float(value) => {
+                let (mantissa, exponent, sign) = value.integer_decode();
+                let magnitude = Magnitude::new(mantissa.into(), multiplier, exponent.into());
+                (sign < 0, magnitude)
+            }
+        };
+        let zero = Magnitude::Finite {
+            top: i32::MIN,
+            bits: (0, 0),
+        };
+        match negative && magnitude != zero {
+            true => Some(Exact::Negative(Reverse(magnitude))),
+            false => Some(Exact::Positive(magnitude)),
+        }
+    }
+
+    /// Returns whether the size is negative, along with the number of bytes in it without
+    /// its sign, truncated and clamped like [`TypedSize::bytes_saturating()`], or the
+    /// value itself if it is NaN or infinite.
+    fn signed_bytes(&self) -> Result<(bool, u128), f64> {
+        let (value, unit) = self.as_unit();
+        let (negative, bytes) = match Scalar::new(value) {
+            Scalar::Integer {
+                negative,
+                magnitude,
+            } => (
+                negative,
+                TypedSize::from_unit(magnitude, unit).bytes_saturating(),
+            ),
+            Scalar::Float(value) if !value.is_finite() => return Err(value),
+            Scalar::Float(value) => {
+                let bytes = TypedSize::from_unit(value.abs(), unit).bytes_saturating();
+                (value < 0.0, bytes)
+            }
+        };
+        Ok((negative && bytes != 0, bytes))
+    }
+
+    /// Returns the number of bytes in the size like [`TypedSize::bytes()`], but clamped to
+    /// the range of a `u128` instead of panicking. Like casting a float to an integer,
+    /// negative sizes and NaN are zero, and sizes too large (including infinity) are
+    /// `u128::MAX`.
+    pub fn bytes_saturating(&self) -> u128 {
+        match self.try_bytes() {
+            Ok(bytes) => bytes,
+            Err(SizeError::Overflow) => u128::MAX,
+            Err(_) => self.as_unit().0.to_f64().map_or(0, |value| value as u128),
+        }
+    }
+
+    /// Formats the size in the given base and style, like [`Size::to_string_with()`].
+    pub fn to_string_with(&self, base: Base, style: Style) -> String {
+        struct Styled<'a, T>(&'a TypedSize<T>, SizeFormatter<'a>);
+
+        impl<T: ToPrimitive> fmt::Display for Styled<'_, T> {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt_with(&self.1, fmt)
+            }
+        }
+
+        let formatter = SizeFormatter::new().with_base(base).with_style(style);
+        Styled(self, formatter).to_string()
+    }
+
+    /// Formats the size in the given base and style, like [`TypedSize::to_string_with()`].
+    #[deprecated(
+        note = "shadows `ToString::to_string`; use `TypedSize::to_string_with` \
+                         instead"
+    )]
+    pub fn to_string(&self, base: Base, style: Style) -> String {
+        self.to_string_with(base, style)
+    }
+
+    /// Formats the size with `formatter`, with a minus sign if it is negative.
+    fn fmt_with(&self, formatter: &SizeFormatter, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.signed_bytes() {
+            Ok((false, bytes)) => {
+                fmt::Display::fmt(&formatter.display(Size::from_bytes(bytes)), fmt)
+            }
+            Ok((true, bytes)) => fmt::Display::fmt(&formatter.display_negative(bytes), fmt),
+            Err(value) => fmt::Display::fmt(&value, fmt),
+        }
+    }
+}
+
+impl<T> TypedSize<T> {
+    /// Returns the value of the size and its unit.
+    pub(crate) fn as_unit(&self) -> (&T, Unit) {
+        use self::TypedSize::*;
+
+        match self {
+            Bytes(value) => (value, Unit::Byte),
+            Kibibytes(value) => (value, Unit::Kibibyte),
+            Kilobytes(value) => (value, Unit::Kilobyte),
+            Mebibytes(value) => (value, Unit::Mebibyte),
+            Megabytes(value) => (value, Unit::Megabyte),
+            Gibibytes(value) => (value, Unit::Gibibyte),
+            Gigabytes(value) => (value, Unit::Gigabyte),
+            Tebibytes(value) => (value, Unit::Tebibyte),
+            Terabytes(value) => (value, Unit::Terabyte),
+            Pebibytes(value) => (value, Unit::Pebibyte),
+            Petabytes(value) => (value, Unit::Petabyte),
+            Exbibytes(value) => (value, Unit::Exbibyte),
+            Exabytes(value) => (value, Unit::Exabyte),
+            Zettabytes(value) => (value, Unit::Zettabyte),
+            Zebibytes(value) => (value, Unit::Zebibyte),
+            Yottabytes(value) => (value, Unit::Yottabyte),
+            Yobibytes(value) => (value, Unit::Yobibyte),
+            Ronnabytes(value) => (value, Unit::Ronnabyte),
+            Robibytes(value) => (value, Unit::Robibyte),
+            Quettabytes(value) => (value, Unit::Quettabyte),
+            Quebibytes(value) => (value, Unit::Quebibyte),
+        }
+    }
+
+    /// Creates a size of `value` in the given unit.
+    ///
+    /// ```
+    /// use size::{TypedSize, Unit};
+    ///
+    /// assert_eq!(TypedSize::from_unit(2.5, Unit::Kibibyte), TypedSize::Bytes(2560));
+    /// ```
+    pub fn from_unit(value: T, unit: Unit) -> Self {
+        use self::TypedSize::*;
+
+        match unit {
+            Unit::Byte => Bytes(value),
+            Unit::Kibibyte => Kibibytes(value),
+            Unit::Kilobyte => Kilobytes(value),
+            Unit::Mebibyte => Mebibytes(value),
+            Unit::Megabyte => Megabytes(value),
+            Unit::Gibibyte => Gibibytes(value),
+            Unit::Gigabyte => Gigabytes(value),
+            Unit::Tebibyte => Tebibytes(value),
+            Unit::Terabyte => Terabytes(value),
+            Unit::Pebibyte => Pebibytes(value),
+            Unit::Petabyte => Petabytes(value),
+            Unit::Exbibyte => Exbibytes(value),
+            Unit::Exabyte => Exabytes(value),
+            Unit::Zettabyte => Zettabytes(value),
+            Unit::Zebibyte => Zebibytes(value),
+            Unit::Yottabyte => Yottabytes(value),
+            Unit::Yobibyte => Yobibytes(value),
+            Unit::Ronnabyte => Ronnabytes(value),
+            Unit::Robibyte => Robibytes(value),
+            Unit::Quettabyte => Quettabytes(value),
+            Unit::Quebibyte => Quebibytes(value),
+        }
+    }
+}
+
+/// Defines deprecated constructors named like the variants of the `Size<T>` enum that
+/// became [`TypedSize`], so that code written against it, e.g. `Size::Kibibytes(4)`,
+/// still compiles.
+macro_rules! variant_constructors {
+    ($($variant:ident),*) => {
+        impl Size {
+            $(
+                #[doc = concat!("Creates a size of [`TypedSize::", stringify!($variant), "`].")]
+                ///
+                /// # Panics
+                ///
+                /// Panics if the size is negative, not finite or more than `u128::MAX` bytes.
+                #[deprecated(note = "`Size` is no longer an enum; use the `Size::from_*` \
+                                     constructors, or `TypedSize` for a size in a unit")]
+                #[allow(non_snake_case)]
+                pub fn $variant<T: ToPrimitive>(value: T) -> Size {
+                    Size::from_bytes(TypedSize::$variant(value).bytes())
+                }
+            )*
+        }
+    };
+}
+
+variant_constructors!(
+    Bytes,
+    Kibibytes,
+    Kilobytes,
+    Mebibytes,
+    Megabytes,
+    Gibibytes,
+    Gigabytes,
+    Tebibytes,
+    Terabytes,
+    Pebibytes,
+    Petabytes,
+    Exbibytes,
+    Exabytes,
+    Zettabytes,
+    Zebibytes,
+    Yottabytes,
+    Yobibytes,
+    Ronnabytes,
+    Robibytes,
+    Quettabytes,
+    Quebibytes
+);
+
+impl<T> TryFrom<TypedSize<T>> for Size
+where
+    T: ToPrimitive,
+{
+    type Error = SizeError;
+
+    /// Counts the bytes in the size, like [`TypedSize::try_bytes()`].
+    ///
+    /// ```
+    /// use size::{Size, SizeError, TypedSize};
+    /// use std::convert::TryFrom;
+    ///
+    /// assert_eq!(Size::try_from(TypedSize::Kibibytes(1.5)), Ok(Size::from_bytes(1536)));
+    /// assert_eq!(Size::try_from(TypedSize::Bytes(-1)), Err(SizeError::Negative));
+    /// ```
+    fn try_from(size: TypedSize<T>) -> Result<Size, SizeError> {
+        size.try_bytes().map(Size::from_bytes)
+    }
+}
+
+impl From<Size> for TypedSize<u128> {
+    fn from(size: Size) -> TypedSize<u128> {
+        TypedSize::Bytes(size.bytes())
+    }
+}
+
+impl<T> FromStr for TypedSize<T>
+where
+    T: FromPrimitive,
+{
+    type Err = ParseError;
+
+    /// Parses a size like [`SizeParser::parse_typed()`] with the default options.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SizeParser::new().parse_typed(s)
+    }
+}